@@ -1,38 +1,104 @@
-use anyhow::Result;
-use reqwest::Client;
+use anyhow::{Result, Context};
+use reqwest::{header, Client, StatusCode};
 use crate::models::{Sdk, SdkVersion};
 use crate::local::scanner::LocalScanner;
-use super::{endpoints::ApiEndpoints, parser::ResponseParser};
+use crate::config::AppConfig;
+use crate::cache::Validators;
+use crate::error::SdkError;
+use super::endpoints::{with_failover, ApiEndpoints, EndpointUnavailable, ServedBy};
+use super::parser::ResponseParser;
+use super::http::{self, ClientKind};
 
 pub struct SdkmanApiClient {
     http_client: Client,
+    /// 按优先级排列的 API 端点（主地址 + 镜像）
+    endpoints: Vec<ApiEndpoints>,
 }
 
 impl SdkmanApiClient {
+    /// 使用已保存的配置创建客户端
     pub fn new() -> Result<Self> {
-        let http_client = Client::builder()
-            .timeout(std::time::Duration::from_secs(30))
-            .user_agent("SDKMAN-GUI/1.0 (Tauri)")
-            .build()?;
-
-        Ok(Self { http_client })
+        let config = AppConfig::load().unwrap_or_default();
+        Self::from_config(&config)
     }
 
-    /// 获取候选列表
-    pub async fn list_candidates(&self) -> Result<Vec<Sdk>> {
-        let url = ApiEndpoints::candidates_list();
-        let response = self.http_client.get(&url).send().await?;
+    /// 使用指定配置创建客户端（配置变化时用于重建）
+    pub fn from_config(config: &AppConfig) -> Result<Self> {
+        let http_client = http::build_client(config, ClientKind::Api)?;
+        let endpoints = ApiEndpoints::api_list(config);
+
+        Ok(Self { http_client, endpoints })
+    }
 
-        if !response.status().is_success() {
-            anyhow::bail!("Failed to fetch candidates: HTTP {}", response.status());
-        }
+    /// 请求文本内容，主地址不可达时切换到镜像
+    ///
+    /// 提供缓存验证信息时发送条件请求，服务器返回 304 表示内容未变化
+    async fn fetch_text(
+        &self,
+        build_url: impl Fn(&ApiEndpoints) -> String,
+        what: &str,
+        validators: Option<&Validators>,
+    ) -> Result<(Fetched<String>, ServedBy)> {
+        let validators = validators.filter(|v| !v.is_empty());
+
+        with_failover(&self.endpoints, |endpoint| {
+            let url = build_url(endpoint);
+            async move {
+                let mut request = self.http_client.get(&url);
+                if let Some(validators) = validators {
+                    if let Some(etag) = &validators.etag {
+                        request = request.header(header::IF_NONE_MATCH, etag);
+                    }
+                    if let Some(last_modified) = &validators.last_modified {
+                        request = request.header(header::IF_MODIFIED_SINCE, last_modified);
+                    }
+                }
+
+                let response = request.send().await?;
+                let status = response.status();
+
+                if status == StatusCode::NOT_MODIFIED && validators.is_some() {
+                    return Ok(Fetched::NotModified);
+                }
+                if status.is_server_error() {
+                    return Err(EndpointUnavailable(status))
+                        .context(format!("Failed to fetch {}", what));
+                }
+                if !status.is_success() {
+                    return Err(SdkError::HttpStatus { status: status.as_u16() })
+                        .context(format!("Failed to fetch {}", what));
+                }
+
+                let header_value = |name| {
+                    response.headers().get(name)
+                        .and_then(|v: &header::HeaderValue| v.to_str().ok())
+                        .map(str::to_string)
+                };
+                let validators = Validators {
+                    etag: header_value(header::ETAG),
+                    last_modified: header_value(header::LAST_MODIFIED),
+                };
+
+                Ok(Fetched::Modified(response.text().await?, validators))
+            }
+        }).await
+    }
 
-        let body = response.text().await?;
-        ResponseParser::parse_candidates(&body)
+    /// 获取候选列表（条件请求，内容未变化时不下载、不解析）
+    pub async fn list_candidates_if_modified(
+        &self,
+        validators: Option<&Validators>,
+    ) -> Result<(Fetched<Vec<Sdk>>, ServedBy)> {
+        let (fetched, served_by) = self.fetch_text(|e| e.candidates_list(), "candidates", validators).await?;
+        Ok((fetched.try_map(|body| ResponseParser::parse_candidates(&body))?, served_by))
     }
 
-    /// 获取版本列表
-    pub async fn list_versions(&self, candidate: &str) -> Result<Vec<SdkVersion>> {
+    /// 获取版本列表（条件请求，内容未变化时不下载、不解析）
+    pub async fn list_versions_if_modified(
+        &self,
+        candidate: &str,
+        validators: Option<&Validators>,
+    ) -> Result<(Fetched<Vec<SdkVersion>>, ServedBy)> {
         let platform = detect_platform();
 
         // 从本地文件系统读取已安装版本和当前版本
@@ -44,15 +110,30 @@ impl SdkmanApiClient {
             .unwrap_or(None)
             .unwrap_or_default();
 
-        let url = ApiEndpoints::versions_list(candidate, &platform, &installed, &current);
-        let response = self.http_client.get(&url).send().await?;
+        let (fetched, served_by) = self.fetch_text(
+            |e| e.versions_list(candidate, &platform, &installed, &current),
+            &format!("versions for {}", candidate),
+            validators,
+        ).await?;
+        Ok((fetched.try_map(|body| ResponseParser::parse_versions(&body, candidate))?, served_by))
+    }
+}
 
-        if !response.status().is_success() {
-            anyhow::bail!("Failed to fetch versions for {}: HTTP {}", candidate, response.status());
-        }
+/// 条件请求结果
+#[derive(Debug)]
+pub enum Fetched<T> {
+    /// 内容已变化，附带新的缓存验证信息
+    Modified(T, Validators),
+    /// 服务器返回 304，缓存仍然有效
+    NotModified,
+}
 
-        let body = response.text().await?;
-        ResponseParser::parse_versions(&body, candidate)
+impl<T> Fetched<T> {
+    fn try_map<U>(self, f: impl FnOnce(T) -> Result<U>) -> Result<Fetched<U>> {
+        Ok(match self {
+            Fetched::Modified(data, validators) => Fetched::Modified(f(data)?, validators),
+            Fetched::NotModified => Fetched::NotModified,
+        })
     }
 }
 
@@ -85,3 +166,68 @@ pub fn detect_platform() -> String {
 
     format!("{}{}", os_prefix, arch_suffix)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpListener;
+
+    const CANDIDATES: &str = "---\nGradle (8.5)  https://gradle.org\n\nBuild automation tool.\n\n$ sdk install gradle\n";
+
+    /// 本地模拟服务器：请求带有匹配的 `If-None-Match` 时返回 304
+    async fn mock_server(etag: &'static str) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+
+        tokio::spawn(async move {
+            while let Ok((mut stream, _)) = listener.accept().await {
+                let mut request = Vec::new();
+                let mut buf = [0u8; 1024];
+                while !request.windows(4).any(|w| w == b"\r\n\r\n") {
+                    let n = stream.read(&mut buf).await.unwrap();
+                    if n == 0 {
+                        break;
+                    }
+                    request.extend_from_slice(&buf[..n]);
+                }
+
+                let request = String::from_utf8_lossy(&request).to_lowercase();
+                let response = if request.contains(&format!("if-none-match: {}", etag)) {
+                    "HTTP/1.1 304 Not Modified\r\nConnection: close\r\n\r\n".to_string()
+                } else {
+                    format!(
+                        "HTTP/1.1 200 OK\r\nETag: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                        etag, CANDIDATES.len(), CANDIDATES
+                    )
+                };
+                stream.write_all(response.as_bytes()).await.unwrap();
+            }
+        });
+
+        format!("http://{}/2", addr)
+    }
+
+    #[tokio::test]
+    async fn test_conditional_request_not_modified() {
+        let config = AppConfig {
+            api_base_url: mock_server("\"v1\"").await,
+            ..AppConfig::default()
+        };
+        let client = SdkmanApiClient::from_config(&config).unwrap();
+
+        let (fetched, _) = client.list_candidates_if_modified(None).await.unwrap();
+        let Fetched::Modified(candidates, validators) = fetched else {
+            panic!("expected full response");
+        };
+        assert_eq!(candidates[0].candidate, "gradle");
+        assert_eq!(validators.etag.as_deref(), Some("\"v1\""));
+
+        let (fetched, _) = client.list_candidates_if_modified(Some(&validators)).await.unwrap();
+        assert!(matches!(fetched, Fetched::NotModified));
+
+        let stale = Validators { etag: Some("\"v0\"".to_string()), last_modified: None };
+        let (fetched, _) = client.list_candidates_if_modified(Some(&stale)).await.unwrap();
+        assert!(matches!(fetched, Fetched::Modified(..)));
+    }
+}
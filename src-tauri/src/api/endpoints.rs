@@ -1,27 +1,237 @@
+use std::fmt;
+use std::future::Future;
+use anyhow::Result;
+use reqwest::{StatusCode, Url};
+use serde::{Deserialize, Serialize};
+use crate::config::AppConfig;
+
+/// 官方 SDKMAN API 地址
+pub const DEFAULT_BASE_URL: &str = "https://api.sdkman.io/2";
+
 /// SDKMAN API 端点
-pub struct ApiEndpoints;
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ApiEndpoints {
+    base_url: String,
+}
 
 impl ApiEndpoints {
-    const BASE_URL: &'static str = "https://api.sdkman.io/2";
+    pub fn new(base_url: &str) -> Self {
+        Self {
+            base_url: base_url.trim().trim_end_matches('/').to_string(),
+        }
+    }
+
+    /// 基础地址
+    pub fn base_url(&self) -> &str {
+        &self.base_url
+    }
 
     /// 获取候选列表: GET /candidates/list
-    pub fn candidates_list() -> String {
-        format!("{}/candidates/list", Self::BASE_URL)
+    pub fn candidates_list(&self) -> String {
+        format!("{}/candidates/list", self.base_url)
     }
 
     /// 获取版本列表: GET /candidates/{candidate}/{platform}/versions/list
-    pub fn versions_list(candidate: &str, platform: &str, installed: &str, current: &str) -> String {
+    pub fn versions_list(&self, candidate: &str, platform: &str, installed: &str, current: &str) -> String {
         format!(
             "{}/candidates/{}/{}/versions/list?installed={}&current={}",
-            Self::BASE_URL, candidate, platform, installed, current
+            self.base_url, candidate, platform, installed, current
         )
     }
 
     /// 下载SDK: GET /broker/download/{candidate}/{version}/{platform}
-    pub fn download(candidate: &str, version: &str, platform: &str) -> String {
+    pub fn download(&self, candidate: &str, version: &str, platform: &str) -> String {
         format!(
             "{}/broker/download/{}/{}/{}",
-            Self::BASE_URL, candidate, version, platform
+            self.base_url, candidate, version, platform
         )
     }
+
+    /// 候选者/版本列表使用的端点：主地址在前，镜像按配置顺序排在后面
+    pub fn api_list(config: &AppConfig) -> Vec<ApiEndpoints> {
+        let primary = non_empty(&config.api_base_url).unwrap_or(DEFAULT_BASE_URL);
+        Self::ordered(primary, &config.mirror_urls)
+    }
+
+    /// 下载（broker）使用的端点：未单独配置时与 API 主地址相同
+    pub fn download_list(config: &AppConfig) -> Vec<ApiEndpoints> {
+        let primary = non_empty(&config.download_base_url)
+            .or_else(|| non_empty(&config.api_base_url))
+            .unwrap_or(DEFAULT_BASE_URL);
+        Self::ordered(primary, &config.mirror_urls)
+    }
+
+    /// 去重并跳过无效地址
+    fn ordered(primary: &str, mirrors: &[String]) -> Vec<ApiEndpoints> {
+        let mut endpoints: Vec<ApiEndpoints> = Vec::new();
+        for url in std::iter::once(primary).chain(mirrors.iter().map(String::as_str)) {
+            let Some(url) = non_empty(url) else {
+                continue;
+            };
+            if Url::parse(url).is_err() {
+                eprintln!("Warning: Ignoring invalid API endpoint: {}", url);
+                continue;
+            }
+            let endpoint = ApiEndpoints::new(url);
+            if !endpoints.contains(&endpoint) {
+                endpoints.push(endpoint);
+            }
+        }
+
+        if endpoints.is_empty() {
+            endpoints.push(ApiEndpoints::new(DEFAULT_BASE_URL));
+        }
+        endpoints
+    }
+}
+
+fn non_empty(value: &str) -> Option<&str> {
+    Some(value.trim()).filter(|v| !v.is_empty())
+}
+
+/// 实际提供响应的端点
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ServedBy {
+    /// 端点基础地址
+    pub endpoint: String,
+    /// 是否为镜像（主地址不可用时切换）
+    pub mirror: bool,
+}
+
+/// 端点使用事件（`api-endpoint`），用于在界面上显示数据来源
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct EndpointUsed {
+    /// 请求类型: candidates, versions, download
+    pub request: String,
+    pub candidate: Option<String>,
+    pub endpoint: String,
+    pub mirror: bool,
+}
+
+impl EndpointUsed {
+    pub fn new(request: &str, candidate: Option<&str>, served_by: &ServedBy) -> Self {
+        Self {
+            request: request.to_string(),
+            candidate: candidate.map(str::to_string),
+            endpoint: served_by.endpoint.clone(),
+            mirror: served_by.mirror,
+        }
+    }
+}
+
+/// 端点返回服务器错误（5xx），可以切换到下一个镜像
+#[derive(Debug, Clone, Copy)]
+pub struct EndpointUnavailable(pub StatusCode);
+
+impl fmt::Display for EndpointUnavailable {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Endpoint unavailable: HTTP {}", self.0)
+    }
+}
+
+impl std::error::Error for EndpointUnavailable {}
+
+/// 判断错误是否表示端点不可达（连接失败、超时或服务器错误）
+pub fn is_unreachable(error: &anyhow::Error) -> bool {
+    error.chain().any(|cause| {
+        if cause.downcast_ref::<EndpointUnavailable>().is_some() {
+            return true;
+        }
+        cause.downcast_ref::<reqwest::Error>()
+            .is_some_and(|e| e.is_connect() || e.is_timeout())
+    })
+}
+
+/// 按顺序尝试各端点，端点不可达时切换到下一个
+///
+/// 其他错误（如 404、解析失败）直接返回，不再尝试镜像
+pub async fn with_failover<T, F, Fut>(endpoints: &[ApiEndpoints], mut request: F) -> Result<(T, ServedBy)>
+where
+    F: FnMut(&ApiEndpoints) -> Fut,
+    Fut: Future<Output = Result<T>>,
+{
+    let mut last_error = None;
+
+    for (index, endpoint) in endpoints.iter().enumerate() {
+        match request(endpoint).await {
+            Ok(value) => {
+                if index > 0 {
+                    println!("Request served by mirror {}", endpoint.base_url());
+                }
+                let served_by = ServedBy {
+                    endpoint: endpoint.base_url().to_string(),
+                    mirror: index > 0,
+                };
+                return Ok((value, served_by));
+            }
+            Err(e) if is_unreachable(&e) => {
+                println!("Endpoint {} unreachable: {:#}", endpoint.base_url(), e);
+                last_error = Some(e);
+            }
+            Err(e) => return Err(e),
+        }
+    }
+
+    Err(last_error.unwrap_or_else(|| anyhow::anyhow!("No API endpoint configured")))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_endpoint_lists() {
+        let config = AppConfig {
+            api_base_url: "https://sdkman.mirror.corp/2/".to_string(),
+            mirror_urls: vec![
+                "https://backup.corp/2".to_string(),
+                "not a url".to_string(),
+                "https://sdkman.mirror.corp/2".to_string(),
+            ],
+            ..AppConfig::default()
+        };
+
+        let api: Vec<String> = ApiEndpoints::api_list(&config).iter().map(|e| e.base_url().to_string()).collect();
+        assert_eq!(api, vec!["https://sdkman.mirror.corp/2", "https://backup.corp/2"]);
+        assert_eq!(
+            ApiEndpoints::api_list(&config)[0].candidates_list(),
+            "https://sdkman.mirror.corp/2/candidates/list"
+        );
+
+        let download = ApiEndpoints::download_list(&AppConfig {
+            download_base_url: "https://downloads.corp/2".to_string(),
+            ..config
+        });
+        assert_eq!(download[0].base_url(), "https://downloads.corp/2");
+
+        let defaults = ApiEndpoints::api_list(&AppConfig::default());
+        assert_eq!(defaults, vec![ApiEndpoints::new(DEFAULT_BASE_URL)]);
+    }
+
+    #[tokio::test]
+    async fn test_failover_to_mirror() {
+        let endpoints = vec![ApiEndpoints::new("https://primary.test/2"), ApiEndpoints::new("https://mirror.test/2")];
+
+        let (value, served_by) = with_failover(&endpoints, |endpoint| {
+            let primary = endpoint.base_url().contains("primary");
+            async move {
+                if primary {
+                    Err(EndpointUnavailable(StatusCode::SERVICE_UNAVAILABLE).into())
+                } else {
+                    Ok(42)
+                }
+            }
+        }).await.unwrap();
+        assert_eq!(value, 42);
+        assert_eq!(served_by, ServedBy { endpoint: "https://mirror.test/2".to_string(), mirror: true });
+
+        // 非连通性错误不切换镜像
+        let mut attempts = 0;
+        let result: Result<((), ServedBy)> = with_failover(&endpoints, |_| {
+            attempts += 1;
+            async { Err(anyhow::anyhow!("HTTP 404")) }
+        }).await;
+        assert!(result.is_err());
+        assert_eq!(attempts, 1);
+    }
 }
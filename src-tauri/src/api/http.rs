@@ -0,0 +1,151 @@
+use std::time::Duration;
+use anyhow::{Result, Context};
+use reqwest::{Client, ClientBuilder, NoProxy, Proxy};
+use reqwest::redirect::Policy;
+use crate::config::{redact_proxy_url, AppConfig, ProxySettings};
+
+const USER_AGENT: &str = "SDKMAN-GUI/1.0 (Tauri)";
+
+/// HTTP 客户端用途（决定超时和重定向策略）
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ClientKind {
+    /// API 请求（候选列表、版本列表）
+    Api,
+    /// 下载归档文件
+    Download,
+    /// 请求 broker 地址（不跟随重定向，用于读取 `X-Sdkman-*` 响应头）
+    Broker,
+}
+
+/// 根据应用配置创建 HTTP 客户端（应用代理设置）
+pub fn build_client(config: &AppConfig, kind: ClientKind) -> Result<Client> {
+    let mut builder = Client::builder()
+        .user_agent(USER_AGENT)
+        .connect_timeout(Duration::from_secs(30)); // 30秒连接超时
+
+    builder = match kind {
+        ClientKind::Api => builder
+            .timeout(Duration::from_secs(30)),
+        ClientKind::Download => builder
+            .timeout(Duration::from_secs(300)) // 5分钟总超时
+            .redirect(Policy::limited(10)), // 允许最多10次重定向
+        ClientKind::Broker => builder
+            .timeout(Duration::from_secs(30))
+            .redirect(Policy::none()),
+    };
+
+    // 应用代理设置
+    builder = apply_proxy(builder, config);
+
+    builder
+        .build()
+        .context("Failed to create HTTP client")
+}
+
+/// 根据配置设置代理
+///
+/// `none` 模式显式禁用代理（包括系统代理）；自动模式没有找到环境变量时
+/// 保留 reqwest 默认的系统代理检测
+fn apply_proxy(builder: ClientBuilder, config: &AppConfig) -> ClientBuilder {
+    if config.proxy_type == "none" {
+        return builder.no_proxy();
+    }
+
+    let Some(settings) = config.proxy_settings() else {
+        return builder;
+    };
+
+    match build_proxies(&settings) {
+        Ok(proxies) => proxies.into_iter().fold(builder, |builder, proxy| builder.proxy(proxy)),
+        Err(e) => {
+            eprintln!("✗ Failed to configure proxy: {}", e);
+            builder
+        }
+    }
+}
+
+/// 根据代理设置创建 reqwest 代理（HTTP 和 HTTPS 使用同一地址时合并为一个）
+fn build_proxies(settings: &ProxySettings) -> Result<Vec<Proxy>> {
+    let no_proxy = settings.no_proxy.as_deref().and_then(NoProxy::from_string);
+
+    let configure = |proxy: Proxy| {
+        let proxy = match &settings.credentials {
+            Some((username, password)) => proxy.basic_auth(username, password),
+            None => proxy,
+        };
+        proxy.no_proxy(no_proxy.clone())
+    };
+
+    let invalid = |url: &str| format!("Invalid proxy URL: {}", redact_proxy_url(url));
+
+    let mut proxies = Vec::new();
+    match (&settings.http, &settings.https) {
+        (Some(http), Some(https)) if http == https => {
+            proxies.push(configure(Proxy::all(http).with_context(|| invalid(http))?));
+        }
+        (http, https) => {
+            if let Some(http) = http {
+                proxies.push(configure(Proxy::http(http).with_context(|| invalid(http))?));
+            }
+            if let Some(https) = https {
+                proxies.push(configure(Proxy::https(https).with_context(|| invalid(https))?));
+            }
+        }
+    }
+
+    Ok(proxies)
+}
+
+/// 打印当前代理配置（诊断用，不包含认证信息）
+pub fn log_proxy_config(config: &AppConfig) {
+    println!("Proxy type: {}", config.proxy_type);
+
+    match config.proxy_settings() {
+        Some(settings) => {
+            if let Some(http) = &settings.http {
+                println!("✓ HTTP proxy: {}", redact_proxy_url(http));
+            }
+            if let Some(https) = &settings.https {
+                println!("✓ HTTPS proxy: {}", redact_proxy_url(https));
+            }
+            if let Some(no_proxy) = &settings.no_proxy {
+                println!("  Bypass: {}", no_proxy);
+            }
+            if settings.credentials.is_some() {
+                println!("  Authentication: configured");
+            }
+        }
+        None if config.proxy_type == "auto" => println!("✗ No proxy environment variables (using system settings)"),
+        None => println!("✗ No proxy configured (direct connection)"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_build_proxies() {
+        let socks = ProxySettings {
+            http: Some("socks5h://proxy:1080".to_string()),
+            https: Some("socks5h://proxy:1080".to_string()),
+            no_proxy: Some("localhost,.internal".to_string()),
+            credentials: Some(("user".to_string(), "pass".to_string())),
+        };
+        assert_eq!(build_proxies(&socks).unwrap().len(), 1);
+
+        let split = ProxySettings {
+            http: Some("http://proxy:3128".to_string()),
+            https: Some("http://secure-proxy:3128".to_string()),
+            ..ProxySettings::default()
+        };
+        assert_eq!(build_proxies(&split).unwrap().len(), 2);
+
+        let invalid = ProxySettings {
+            http: Some("http://user:secret@[bad".to_string()),
+            ..ProxySettings::default()
+        };
+        let error = build_proxies(&invalid).unwrap_err().to_string();
+        assert!(!error.contains("secret"));
+    }
+}
@@ -1,5 +1,7 @@
 mod client;
 mod parser;
 pub mod endpoints;
+pub mod http;
+pub mod probe;
 
-pub use client::{SdkmanApiClient, detect_platform};
\ No newline at end of file
+pub use client::{SdkmanApiClient, Fetched, detect_platform};
\ No newline at end of file
@@ -1,228 +1,501 @@
-use crate::models::{Sdk, SdkVersion, JdkCategory};
+use crate::models::{Sdk, SdkVersion, JdkCategory, is_lts_release, parse_java_major};
 use anyhow::Result;
-use regex::Regex;
-use lazy_static::lazy_static;
-
-lazy_static! {
-    /// 正则表达式：解析候选列列表
-    /// 格式: ---\n候选名称(版本)  网址\n\n描述...\n$ sdk install candidate_id\n
-    static ref CANDIDATE_PATTERN: Regex = Regex::new(
-        r"(?s)---\r*\n(.+?)\r*\n\r*\n(.*?)\$ sdk install(.*?)\r*\n"
-    ).unwrap();
-
-    /// 正则表达式：解析Java版本表格（带|分隔符）
-    static ref JAVA_VERSION_PATTERN: Regex = Regex::new(
-        r"(.*?)\|(.*?)\|(.*?)\|(.*?)\|(.*?)\|(.*)"
-    ).unwrap();
-}
+use crate::error::SdkError;
+use crate::utils::version;
 
-const VENDOR_HEADER_NAME: &str = "Vendor";
+/// 候选列表中分隔各条目的横线
+const ENTRY_RULE: char = '-';
+/// 版本列表中分隔标题、正文和图例的双横线
+const SECTION_RULE: char = '=';
+/// 安装命令前缀，每个候选条目以此结尾
+const INSTALL_COMMAND: &str = "$ sdk install";
 
 pub struct ResponseParser;
 
+/// 版本表格（Java 格式）的列位置，由表头确定
+struct TableColumns {
+    count: usize,
+    vendor: Option<usize>,
+    use_marker: Option<usize>,
+    version: usize,
+    dist: Option<usize>,
+    status: Option<usize>,
+    identifier: usize,
+}
+
+impl TableColumns {
+    /// 从表头行解析列位置（如 `Vendor | Use | Version | Dist | Status | Identifier`）
+    fn from_header(line: &str) -> Option<Self> {
+        let names: Vec<String> = line.split('|').map(|c| c.trim().to_ascii_lowercase()).collect();
+        let position = |name: &str| names.iter().position(|n| n == name);
+
+        Some(Self {
+            count: names.len(),
+            vendor: position("vendor"),
+            use_marker: position("use"),
+            version: position("version")?,
+            dist: position("dist"),
+            status: position("status"),
+            identifier: position("identifier")?,
+        })
+    }
+}
+
+/// 版本前的状态标记（`>` 正在使用、`*` 已安装、`+` 仅本地）
+#[derive(Default)]
+struct Markers {
+    in_use: bool,
+    installed: bool,
+    local: bool,
+}
+
+impl Markers {
+    fn apply(&mut self, marker: char) {
+        match marker {
+            '>' => self.in_use = true,
+            '*' => self.installed = true,
+            '+' => self.local = true,
+            _ => {}
+        }
+    }
+}
+
 impl ResponseParser {
-    /// 解析候选列表响应（表格格式）
-    /// 使用正则表达式解析，参考 sdkman-ui 的实现
-    /// 格式示例：
-    /// ```
+    /// 解析候选列表响应
+    ///
+    /// 条目之间以横线分隔，每个条目由标题行、描述和安装命令组成：
+    /// ```text
     /// --------------------------------------------------------------------------------
     /// Apache ActiveMQ (Classic) (5.17.1)                  https://activemq.apache.org/
+    ///
     /// Apache ActiveMQ® is a popular open source...
+    ///
     ///                                                   $ sdk install activemq
     /// --------------------------------------------------------------------------------
     /// ```
     pub fn parse_candidates(response: &str) -> Result<Vec<Sdk>> {
-        let mut sdks = Vec::new();
+        const WHAT: &str = "candidate list";
 
         if response.trim().is_empty() {
-            return Ok(sdks);
+            return Err(SdkError::malformed_response(WHAT, "empty response").into());
         }
 
-        for cap in CANDIDATE_PATTERN.captures_iter(response) {
-            // group 1: 候选名称（包含版本和网址的第一行）
-            // group 2: 描述文本
-            // group 3: candidate ID（安装命令中的标识符）
-            let mut first_line = cap.get(1).map(|m| m.as_str().trim()).unwrap_or("");
-            let description = cap.get(2)
-                .map(|m| m.as_str().trim().replace('\n', " "))
-                .unwrap_or_default();
-            let candidate_id = cap.get(3).map(|m| m.as_str().trim()).unwrap_or("");
-
-            let mut sdk = Sdk {
-                candidate: candidate_id.to_string(),
-                name: String::new(),
-                description: description.clone(),
-                website: None,
-                latest_version: None,
-                installed_version: None,
-                installed: false,
-                category: crate::models::Category::from_name(candidate_id, &description),
-            };
-
-            // 提取网址（最后一个 http 开头的部分）
-            if let Some(http_index) = first_line.rfind("http") {
-                sdk.website = Some(first_line[http_index..].trim().to_string());
-                first_line = first_line[..http_index].trim();
-            }
+        let lines: Vec<&str> = response.lines().collect();
+        let mut sdks = Vec::new();
 
-            // 提取版本（最后一对括号中的内容）
-            if let Some(last_open_paren) = first_line.rfind('(') {
-                if let Some(last_close_paren) = first_line.rfind(')') {
-                    if last_close_paren > last_open_paren {
-                        sdk.latest_version = Some(
-                            first_line[last_open_paren + 1..last_close_paren]
-                                .trim()
-                                .to_string()
-                        );
-                        first_line = first_line[..last_open_paren].trim();
-                    }
+        // 第一条横线之前是标题和帮助信息
+        let mut entry_start = None;
+        for (index, line) in lines.iter().enumerate() {
+            if is_rule(line, ENTRY_RULE) {
+                if let Some(start) = entry_start {
+                    sdks.extend(Self::parse_candidate_entry(&lines[start..index], start)?);
                 }
+                entry_start = Some(index + 1);
             }
+        }
+        if let Some(start) = entry_start {
+            sdks.extend(Self::parse_candidate_entry(&lines[start..], start)?);
+        }
 
-            // 剩下的就是名称
-            sdk.name = first_line.to_string();
-
-            sdks.push(sdk);
+        if sdks.is_empty() {
+            return Err(SdkError::malformed_response(WHAT, "no candidate entries found").into());
         }
 
         Ok(sdks)
     }
 
-    /// 解析版本列表响应（表格格式）
-    /// 使用正则表达式解析，参考 sdkman-ui 的实现
+    /// 解析单个候选条目，空条目返回 None
+    fn parse_candidate_entry(lines: &[&str], offset: usize) -> Result<Option<Sdk>> {
+        let content: Vec<(usize, &str)> = lines
+            .iter()
+            .enumerate()
+            .map(|(i, line)| (offset + i + 1, line.trim()))
+            .filter(|(_, line)| !line.is_empty())
+            .collect();
+
+        let Some(&(first_line_no, header)) = content.first() else {
+            return Ok(None);
+        };
+
+        let Some(install_index) = content.iter().position(|(_, line)| line.starts_with(INSTALL_COMMAND)) else {
+            return Err(SdkError::malformed_response(
+                "candidate list",
+                format!("entry at line {} has no `{}` command", first_line_no, INSTALL_COMMAND),
+            ).into());
+        };
+
+        let (install_line_no, install_line) = content[install_index];
+        let Some(candidate_id) = install_line[INSTALL_COMMAND.len()..].split_whitespace().next() else {
+            return Err(SdkError::malformed_response(
+                "candidate list",
+                format!("install command at line {} has no candidate", install_line_no),
+            ).into());
+        };
+
+        // 标题行之后、安装命令之前的内容是描述
+        let description = content
+            .get(1..install_index)
+            .unwrap_or_default()
+            .iter()
+            .map(|(_, line)| *line)
+            .collect::<Vec<_>>()
+            .join(" ");
+
+        let mut sdk = Sdk {
+            candidate: candidate_id.to_string(),
+            name: String::new(),
+            description: description.clone(),
+            website: None,
+            latest_version: None,
+            installed_version: None,
+            installed: false,
+            category: crate::models::Category::from_name(candidate_id, &description),
+        };
+
+        // 条目只有安装命令时，使用 candidate ID 作为名称
+        if install_index == 0 {
+            sdk.name = candidate_id.to_string();
+            return Ok(Some(sdk));
+        }
+
+        let mut title = header;
+
+        // 提取网址（最后一个 http 开头的部分）
+        if let Some(http_index) = title.rfind("http") {
+            sdk.website = Some(title[http_index..].trim().to_string());
+            title = title[..http_index].trim();
+        }
+
+        // 提取版本（最后一对括号中的内容）
+        if let (Some(open), Some(close)) = (title.rfind('('), title.rfind(')')) {
+            if close > open {
+                sdk.latest_version = Some(title[open + 1..close].trim().to_string());
+                title = title[..open].trim();
+            }
+        }
+
+        // 剩下的就是名称
+        sdk.name = if title.is_empty() { candidate_id.to_string() } else { title.to_string() };
+
+        Ok(Some(sdk))
+    }
+
+    /// 解析版本列表响应
+    ///
+    /// 有 `Identifier` 列表头时按表格解析（Java），否则按多列版本号解析（Gradle、Maven 等）
     pub fn parse_versions(response: &str, candidate: &str) -> Result<Vec<SdkVersion>> {
+        let what = format!("{} version list", candidate);
+
         if response.trim().is_empty() {
-            return Ok(vec![]);
+            return Err(SdkError::malformed_response(&what, "empty response").into());
         }
 
-        // 检测是Java格式还是其他格式
-        if JAVA_VERSION_PATTERN.is_match(response) && response.contains(VENDOR_HEADER_NAME) {
-            // Java格式：带|分隔符的表格
-            Self::parse_java_versions(response, candidate)
-        } else {
-            // 其他SDK格式：空格分隔
-            Self::parse_other_versions(response, candidate)
-        }
+        let lines: Vec<&str> = response.lines().collect();
+        let header = lines
+            .iter()
+            .position(|line| line.contains('|') && line.to_ascii_lowercase().contains("identifier"));
+
+        let mut versions = match header {
+            Some(index) => Self::parse_version_table(&lines, index, candidate, &what)?,
+            None => Self::parse_version_columns(&lines, candidate, &what)?,
+        };
+
+        // 服务器按列排列版本，统一按版本号排序（新版本在前）
+        version::sort_descending(&mut versions, |v| v.identifier.as_str());
+
+        Ok(versions)
     }
 
-    /// 解析Java版本（表格格式，|分隔）
+    /// 解析表格格式（Java）
     /// 格式: Vendor | Use | Version | Dist | Status | Identifier
-    fn parse_java_versions(response: &str, candidate: &str) -> Result<Vec<SdkVersion>> {
-        let mut versions = Vec::new();
-        let mut last_vendor: Option<String> = None;
+    fn parse_version_table(
+        lines: &[&str],
+        header_index: usize,
+        candidate: &str,
+        what: &str,
+    ) -> Result<Vec<SdkVersion>> {
+        let Some(columns) = TableColumns::from_header(lines[header_index]) else {
+            return Err(SdkError::malformed_response(
+                what,
+                format!("table header at line {} has no Version or Identifier column", header_index + 1),
+            ).into());
+        };
 
-        for cap in JAVA_VERSION_PATTERN.captures_iter(response) {
-            let vendor_col = cap.get(1).map(|m| m.as_str().trim()).unwrap_or("");
-            let use_col = cap.get(2).map(|m| m.as_str().trim()).unwrap_or("");
-            let version_col = cap.get(3).map(|m| m.as_str().trim()).unwrap_or("");
-            let status_col = cap.get(5).map(|m| m.as_str().trim()).unwrap_or("");
-            let identifier_col = cap.get(6).map(|m| m.as_str().trim()).unwrap_or("");
+        let mut versions = Vec::new();
+        let mut last_vendor = String::new();
 
-            // 跳过表头
-            if vendor_col == VENDOR_HEADER_NAME {
+        for (index, line) in lines.iter().enumerate().skip(header_index + 1) {
+            if is_rule(line, SECTION_RULE) {
+                break;
+            }
+            if line.trim().is_empty() || is_rule(line, ENTRY_RULE) {
                 continue;
             }
 
-            // 处理vendor（可能为空，使用上一行的vendor）
-            let vendor = if !vendor_col.is_empty() {
-                last_vendor = Some(vendor_col.to_string());
-                vendor_col.to_string()
-            } else if let Some(ref lv) = last_vendor {
-                lv.clone()
-            } else {
-                String::new()
-            };
-
-            // identifier 必须非��
-            if identifier_col.is_empty() {
+            let cells: Vec<&str> = line.split('|').map(str::trim).collect();
+            if cells.len() < columns.count {
+                return Err(SdkError::malformed_response(
+                    what,
+                    format!("row at line {} has {} columns, expected {}", index + 1, cells.len(), columns.count),
+                ).into());
+            }
+            let cell = |column: Option<usize>| column.map(|i| cells[i]).unwrap_or("");
+
+            // identifier 必须非空
+            let identifier = cells[columns.identifier];
+            if identifier.is_empty() {
                 continue;
             }
+            if !is_version_token(identifier) {
+                return Err(SdkError::malformed_response(
+                    what,
+                    format!("invalid identifier {:?} at line {}", identifier, index + 1),
+                ).into());
+            }
+
+            // 同一供应商只在第一行显示名称
+            let vendor_cell = cell(columns.vendor);
+            if !vendor_cell.is_empty() {
+                last_vendor = vendor_cell.to_string();
+            }
 
             // 解析状态
-            let is_installed = status_col.contains("installed") || use_col.contains('*');
-            let is_in_use = use_col.contains('>');
-
-            let sdk_version = SdkVersion {
-                version: version_col.to_string(),
-                identifier: identifier_col.to_string(),
-                vendor,
-                categories: JdkCategory::from_identifier(identifier_col),
+            let use_marker = cell(columns.use_marker);
+            let status = cell(columns.status);
+            let is_installed = status.contains("installed") || status.contains("local only") || use_marker.contains('*');
+            let is_in_use = use_marker.contains('>');
+
+            let version_text = cells[columns.version];
+            let major_version = parse_java_major(version_text);
+
+            versions.push(SdkVersion {
+                version: version_text.to_string(),
+                identifier: identifier.to_string(),
+                vendor: last_vendor.clone(),
+                dist: cell(columns.dist).to_string(),
+                major_version,
+                lts: major_version.is_some_and(is_lts_release),
+                categories: JdkCategory::from_identifier(identifier),
                 candidate: candidate.to_string(),
-                installed: is_installed,
+                installed: is_installed || is_in_use,
                 is_default: is_in_use,
                 in_use: is_in_use,
                 installing: None,
                 install_progress: None,
-            };
-
-            versions.push(sdk_version);
+                checksum: None,
+                install_kind: None,
+                link_target: None,
+            });
         }
 
         Ok(versions)
     }
 
-    /// 解析其他SDK版本（空格分隔格式）
-    /// 参考 sdkman-ui 的实现逻辑
-    fn parse_other_versions(response: &str, candidate: &str) -> Result<Vec<SdkVersion>> {
+    /// 解析多列格式（Gradle、Maven 等）
+    ///
+    /// 版本位于标题和图例两条双横线之间，每个版本前可能有 `>`、`*`、`+` 标记：
+    /// ```text
+    ///  > * 8.4                 7.6.2               6.9.2               5.0
+    /// ```
+    fn parse_version_columns(lines: &[&str], candidate: &str, what: &str) -> Result<Vec<SdkVersion>> {
+        let rules: Vec<usize> = lines
+            .iter()
+            .enumerate()
+            .filter(|(_, line)| is_rule(line, SECTION_RULE))
+            .map(|(index, _)| index)
+            .collect();
+
+        if rules.len() < 2 {
+            return Err(SdkError::malformed_response(what, "missing version list header").into());
+        }
+        let end = rules.get(2).copied().unwrap_or(lines.len());
+
         let mut versions = Vec::new();
-        let lines: Vec<&str> = response.lines().collect();
+        for (offset, line) in lines[rules[1] + 1..end].iter().enumerate() {
+            let mut markers = Markers::default();
+
+            for token in line.split_whitespace() {
+                let version_text = token.trim_start_matches(['>', '*', '+']);
+                token[..token.len() - version_text.len()]
+                    .chars()
+                    .for_each(|marker| markers.apply(marker));
+
+                // 单独的标记作用于下一个版本
+                if version_text.is_empty() {
+                    continue;
+                }
+                if !is_version_token(version_text) {
+                    return Err(SdkError::malformed_response(
+                        what,
+                        format!("invalid version {:?} at line {}", version_text, rules[1] + offset + 2),
+                    ).into());
+                }
 
-        for (line_num, line) in lines.iter().enumerate() {
-            // 跳过表头（前3行）
-            if line_num < 3 {
-                continue;
+                let marked = std::mem::take(&mut markers);
+                versions.push(SdkVersion {
+                    version: version_text.to_string(),
+                    identifier: version_text.to_string(),
+                    vendor: String::new(),
+                    dist: String::new(),
+                    major_version: None,
+                    lts: false,
+                    categories: Vec::new(),
+                    candidate: candidate.to_string(),
+                    installed: marked.installed || marked.local || marked.in_use,
+                    is_default: marked.in_use,
+                    in_use: marked.in_use,
+                    installing: None,
+                    install_progress: None,
+                    checksum: None,
+                    install_kind: None,
+                    link_target: None,
+                });
             }
+        }
 
-            // 跳过分隔符行
-            if line.trim().starts_with("=====") {
-                break;
-            }
+        Ok(versions)
+    }
+}
 
-            // 跳过空行
-            if line.trim().is_empty() {
-                continue;
-            }
+/// 版本号必须以 ASCII 字母或数字开头（排除乱码和未识别的标记）
+fn is_version_token(token: &str) -> bool {
+    token.chars().next().is_some_and(|c| c.is_ascii_alphanumeric())
+}
 
-            // 每行开头的5个字符是标记区域，格式如: " > * " 或 "   * " 或 "     "
-            let marker_area = if line.len() >= 5 {
-                &line[0..5]
-            } else {
-                line
-            };
+/// 是否为由指定字符组成的分隔线
+fn is_rule(line: &str, rule: char) -> bool {
+    let line = line.trim();
+    line.len() >= 3 && line.chars().all(|c| c == rule)
+}
 
-            let is_in_use = marker_area.contains('>');
-            let is_installed = marker_area.contains('*');
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::{CommandError, ErrorCode};
+
+    fn fixture(name: &str) -> &'static str {
+        match name {
+            "candidates_list" => include_str!("../../tests/fixtures/sdkman/candidates_list.txt"),
+            "java_versions" => include_str!("../../tests/fixtures/sdkman/java_versions.txt"),
+            "java_versions_empty" => include_str!("../../tests/fixtures/sdkman/java_versions_empty.txt"),
+            "gradle_versions" => include_str!("../../tests/fixtures/sdkman/gradle_versions.txt"),
+            "gradle_versions_empty" => include_str!("../../tests/fixtures/sdkman/gradle_versions_empty.txt"),
+            "kotlin_versions" => include_str!("../../tests/fixtures/sdkman/kotlin_versions.txt"),
+            "maven_versions_crlf" => include_str!("../../tests/fixtures/sdkman/maven_versions_crlf.txt"),
+            _ => panic!("unknown fixture {}", name),
+        }
+    }
 
-            // 提取版本区域（从第5个字符开始），按空格分割
-            let versions_area = if line.len() > 5 { &line[5..] } else { "" };
+    fn find<'a>(versions: &'a [SdkVersion], identifier: &str) -> &'a SdkVersion {
+        versions.iter().find(|v| v.identifier == identifier).unwrap()
+    }
 
-            // 按多个空格分割，提取所有版本��
-            let version_parts: Vec<&str> = versions_area
-                .split_whitespace()
-                .filter(|s| !s.is_empty())
-                .collect();
+    #[test]
+    fn test_parse_candidates_fixture() {
+        let sdks = ResponseParser::parse_candidates(fixture("candidates_list")).unwrap();
+        let ids: Vec<&str> = sdks.iter().map(|s| s.candidate.as_str()).collect();
+        assert_eq!(ids, vec!["activemq", "gradle", "java", "kotlin", "maven", "springboot"]);
+
+        let activemq = &sdks[0];
+        assert_eq!(activemq.name, "Apache ActiveMQ (Classic)");
+        assert_eq!(activemq.latest_version.as_deref(), Some("5.17.1"));
+        assert_eq!(activemq.website.as_deref(), Some("https://activemq.apache.org/"));
+        assert!(activemq.description.starts_with("Apache ActiveMQ® is a popular"));
+        assert!(activemq.description.ends_with("languages and platforms."));
+
+        assert_eq!(sdks[2].latest_version.as_deref(), Some("21.0.2-tem"));
+        assert_eq!(sdks[5].name, "Spring Boot");
+
+        // CRLF 换行得到相同结果
+        let crlf = fixture("candidates_list").replace('\n', "\r\n");
+        let sdks_crlf = ResponseParser::parse_candidates(&crlf).unwrap();
+        assert_eq!(sdks_crlf.len(), sdks.len());
+        assert_eq!(sdks_crlf[0].description, activemq.description);
+    }
 
-            // 只有第一个版本带标记，其他版本不带标记
-            for (idx, version_text) in version_parts.iter().enumerate() {
-                let is_first = idx == 0;
+    #[test]
+    fn test_parse_java_versions_fixture() {
+        let versions = ResponseParser::parse_versions(fixture("java_versions"), "java").unwrap();
+        assert_eq!(versions.len(), 15);
+        assert_eq!(versions[0].identifier, "23.1.2.r21-mandrel");
+        assert_eq!(versions.last().unwrap().identifier, "custom-jdk");
+
+        let temurin = find(&versions, "21.0.2-tem");
+        assert_eq!(temurin.vendor, "Temurin");
+        assert_eq!(temurin.dist, "tem");
+        assert_eq!(temurin.major_version, Some(21));
+        assert!(temurin.lts && temurin.in_use && temurin.installed);
+
+        // 供应商列为空时沿用上一行
+        let temurin_11 = find(&versions, "11.0.22-tem");
+        assert_eq!(temurin_11.vendor, "Temurin");
+        assert!(!temurin_11.installed);
+        assert!(find(&versions, "17.0.10-tem").installed);
+
+        let gluon = find(&versions, "22.1.0.1.r17-gln");
+        assert_eq!((gluon.major_version, gluon.lts), (Some(17), true));
+        let zulu_ea = find(&versions, "22.ea.30-zulu");
+        assert_eq!((zulu_ea.major_version, zulu_ea.lts), (Some(22), false));
+        assert!(find(&versions, "custom-jdk").installed);
+
+        let crlf = fixture("java_versions").replace('\n', "\r\n");
+        assert_eq!(ResponseParser::parse_versions(&crlf, "java").unwrap().len(), 15);
+    }
 
-                let sdk_version = SdkVersion {
-                    version: version_text.to_string(),
-                    identifier: version_text.to_string(),
-                    vendor: String::new(),
-                    categories: Vec::new(),
-                    candidate: candidate.to_string(),
-                    installed: if is_first { is_installed || is_in_use } else { false },
-                    is_default: if is_first { is_in_use } else { false },
-                    in_use: if is_first { is_in_use } else { false },
-                    installing: None,
-                    install_progress: None,
-                };
+    #[test]
+    fn test_parse_column_versions_fixtures() {
+        let gradle = ResponseParser::parse_versions(fixture("gradle_versions"), "gradle").unwrap();
+        assert_eq!(gradle.len(), 32);
+        assert_eq!(gradle[0].identifier, "8.6");
+        let current = find(&gradle, "8.4");
+        assert!(current.in_use && current.installed);
+        // 标记可以出现在任意一列
+        assert!(find(&gradle, "7.6.1").installed);
+        assert!(!find(&gradle, "7.6.1").in_use);
+        assert!(find(&gradle, "6.8.1-local").installed);
+        assert!(!find(&gradle, "8.3").installed);
+
+        let maven = ResponseParser::parse_versions(fixture("maven_versions_crlf"), "maven").unwrap();
+        assert_eq!(maven.len(), 12);
+        assert_eq!(maven[0].identifier, "4.0.0-alpha-12");
+        assert!(find(&maven, "3.9.5").in_use);
+        assert!(find(&maven, "3.9.3").installed);
+        assert!(maven.iter().all(|v| !v.identifier.ends_with('\r')));
+
+        let kotlin = ResponseParser::parse_versions(fixture("kotlin_versions"), "kotlin").unwrap();
+        assert_eq!(kotlin.len(), 16);
+        assert_eq!(kotlin[0].identifier, "2.0.0-Beta3");
+        assert!(kotlin.iter().all(|v| !v.installed));
+    }
 
-                versions.push(sdk_version);
-            }
+    #[test]
+    fn test_parse_empty_tables() {
+        assert!(ResponseParser::parse_versions(fixture("java_versions_empty"), "java").unwrap().is_empty());
+        assert!(ResponseParser::parse_versions(fixture("gradle_versions_empty"), "gradle").unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_malformed_responses() {
+        let malformed = [
+            ResponseParser::parse_candidates("").map(|_| ()),
+            ResponseParser::parse_candidates("<html>Service Unavailable</html>").map(|_| ()),
+            ResponseParser::parse_candidates("----------\nJava (21)\n\nno install line\n----------\n").map(|_| ()),
+            ResponseParser::parse_versions("  \r\n", "gradle").map(|_| ()),
+            ResponseParser::parse_versions("<html>Bad Gateway</html>", "gradle").map(|_| ()),
+            ResponseParser::parse_versions(" Vendor | Use | Version | Dist | Status | Identifier\n Temurin | 21.0.2\n", "java").map(|_| ()),
+        ];
+        for result in malformed {
+            let error = CommandError::from(result.unwrap_err());
+            assert_eq!(error.code, ErrorCode::MalformedResponse, "{}", error);
         }
 
-        Ok(versions)
+        // 多字节字符不会导致 panic，不是版本号的内容作为格式错误报告
+        let multibyte = "==========\nAvailable Versions\n==========\n  éé 1.0  ✓2.0\n==========\n";
+        let error = CommandError::from(ResponseParser::parse_versions(multibyte, "demo").unwrap_err());
+        assert_eq!(error.code, ErrorCode::MalformedResponse);
+        assert!(error.message.contains("\"éé\""), "{}", error.message);
+
+        let versions = ResponseParser::parse_versions("==========\nAvailable Versions\n==========\n  > * 1.0  2.0\n==========\n", "demo").unwrap();
+        assert_eq!(versions.len(), 2);
     }
 }
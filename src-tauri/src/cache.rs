@@ -1,17 +1,137 @@
 use anyhow::{Context, Result};
 use serde::{Deserialize, Serialize};
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use crate::api::detect_platform;
 use crate::models::{SdkVersion, Sdk};
+use crate::config::AppConfig;
+use crate::local::usage::SizeIndex;
 
-const JDK_VERSIONS_CACHE: &str = "jdk_versions.json";
 const SDK_CANDIDATES_CACHE: &str = "sdk_candidates.json";
+/// 版本列表缓存目录，按平台分子目录：`versions/<platform>/<candidate>.json`
+const VERSIONS_CACHE_DIR: &str = "versions";
+/// 版本目录大小缓存文件
+const DISK_USAGE_CACHE: &str = "disk_usage.json";
+/// 旧版本未区分平台的缓存文件：`jdk_versions.json`、`<candidate>_versions.json`
+const LEGACY_VERSIONS_SUFFIX: &str = "_versions.json";
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct SdkVersionsCache {
     pub candidate: String,
+    /// 缓存对应的平台（`detect_platform()` 的返回值）
+    #[serde(default)]
+    pub platform: String,
     pub versions: Vec<SdkVersion>,
     pub timestamp: u64,
+    #[serde(default)]
+    pub validators: Validators,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SdkCandidatesCache {
+    pub candidates: Vec<Sdk>,
+    pub timestamp: u64,
+    #[serde(default)]
+    pub validators: Validators,
+}
+
+/// HTTP 缓存验证信息，用于条件请求（`If-None-Match` / `If-Modified-Since`）
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Validators {
+    pub etag: Option<String>,
+    pub last_modified: Option<String>,
+}
+
+impl Validators {
+    pub fn is_empty(&self) -> bool {
+        self.etag.is_none() && self.last_modified.is_none()
+    }
+}
+
+/// 候选者缓存文件格式（旧版本只保存列表，没有时间戳）
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum StoredCandidates {
+    Current(SdkCandidatesCache),
+    Legacy(Vec<Sdk>),
+}
+
+/// 缓存类型（决定使用哪个有效期）
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CacheKind {
+    Candidates,
+    Versions,
+}
+
+impl CacheKind {
+    /// 配置的有效期（秒）
+    pub fn ttl_secs(self, config: &AppConfig) -> u64 {
+        let hours = match self {
+            CacheKind::Candidates => config.candidates_cache_ttl_hours,
+            CacheKind::Versions => config.versions_cache_ttl_hours,
+        };
+        hours.saturating_mul(3600)
+    }
+}
+
+/// 缓存标识，例如 `candidates`、`versions:java`
+pub fn cache_key(kind: CacheKind, candidate: Option<&str>) -> String {
+    match (kind, candidate) {
+        (CacheKind::Candidates, _) => "candidates".to_string(),
+        (CacheKind::Versions, Some(candidate)) => format!("versions:{}", candidate),
+        (CacheKind::Versions, None) => "versions".to_string(),
+    }
+}
+
+/// 读取到的缓存数据及其获取时间
+#[derive(Debug)]
+pub struct Cached<T> {
+    pub data: T,
+    pub timestamp: u64,
+    pub validators: Validators,
+}
+
+impl<T> Cached<T> {
+    /// 缓存是否已超过有效期
+    pub fn is_stale(&self, ttl_secs: u64) -> bool {
+        is_stale_at(self.timestamp, ttl_secs, get_timestamp())
+    }
+}
+
+fn is_stale_at(timestamp: u64, ttl_secs: u64, now: u64) -> bool {
+    now.saturating_sub(timestamp) >= ttl_secs
+}
+
+/// 缓存状态（供界面显示最后获取时间）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CacheInfo {
+    pub kind: CacheKind,
+    pub candidate: Option<String>,
+    /// 获取时间（Unix 时间戳，秒；旧格式缓存为 0）
+    pub fetched_at: u64,
+    pub ttl_secs: u64,
+    pub stale: bool,
+    /// 最近一次获取失败（或处于离线模式），显示的是缓存数据
+    pub offline: bool,
+}
+
+/// 后台刷新完成事件（`cache-updated`）
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CacheUpdated {
+    pub kind: CacheKind,
+    pub candidate: Option<String>,
+    pub fetched_at: u64,
+}
+
+impl CacheUpdated {
+    pub fn new(kind: CacheKind, candidate: Option<&str>) -> Self {
+        Self {
+            kind,
+            candidate: candidate.map(str::to_string),
+            fetched_at: get_timestamp(),
+        }
+    }
 }
 
 /// 获取缓存目录路径（与配置文件相同的目录）
@@ -31,6 +151,14 @@ fn get_cache_dir() -> Result<PathBuf> {
     Ok(cache_dir)
 }
 
+/// 版本列表缓存文件路径（按候选者和平台区分）
+fn versions_cache_path(cache_dir: &Path, candidate: &str, platform: &str) -> PathBuf {
+    cache_dir
+        .join(VERSIONS_CACHE_DIR)
+        .join(platform)
+        .join(format!("{}.json", candidate))
+}
+
 /// 获取当前时间戳（秒）
 fn get_timestamp() -> u64 {
     std::time::SystemTime::now()
@@ -39,50 +167,18 @@ fn get_timestamp() -> u64 {
         .as_secs()
 }
 
-/// 缓存JDK版本列表
-pub fn cache_jdk_versions(versions: &[SdkVersion]) -> Result<()> {
+/// 缓存SDK候选者列表
+pub fn cache_sdk_candidates(candidates: &[Sdk], validators: &Validators) -> Result<()> {
     let cache_dir = get_cache_dir()?;
-    let cache_file = cache_dir.join(JDK_VERSIONS_CACHE);
+    let cache_file = cache_dir.join(SDK_CANDIDATES_CACHE);
 
-    let cache_data = SdkVersionsCache {
-        candidate: "java".to_string(),
-        versions: versions.to_vec(),
+    let cache_data = SdkCandidatesCache {
+        candidates: candidates.to_vec(),
         timestamp: get_timestamp(),
+        validators: validators.clone(),
     };
 
     let json = serde_json::to_string_pretty(&cache_data)
-        .context("Failed to serialize JDK versions cache")?;
-
-    fs::write(&cache_file, json)
-        .context("Failed to write JDK versions cache")?;
-
-    Ok(())
-}
-
-/// 读取JDK版本缓存
-pub fn read_jdk_versions_cache() -> Result<Option<Vec<SdkVersion>>> {
-    let cache_dir = get_cache_dir()?;
-    let cache_file = cache_dir.join(JDK_VERSIONS_CACHE);
-
-    if !cache_file.exists() {
-        return Ok(None);
-    }
-
-    let content = fs::read_to_string(&cache_file)
-        .context("Failed to read JDK versions cache")?;
-
-    let cache_data: SdkVersionsCache = serde_json::from_str(&content)
-        .context("Failed to parse JDK versions cache")?;
-
-    Ok(Some(cache_data.versions))
-}
-
-/// 缓存SDK候选者列表
-pub fn cache_sdk_candidates(candidates: &[Sdk]) -> Result<()> {
-    let cache_dir = get_cache_dir()?;
-    let cache_file = cache_dir.join(SDK_CANDIDATES_CACHE);
-
-    let json = serde_json::to_string_pretty(candidates)
         .context("Failed to serialize SDK candidates cache")?;
 
     fs::write(&cache_file, json)
@@ -92,7 +188,7 @@ pub fn cache_sdk_candidates(candidates: &[Sdk]) -> Result<()> {
 }
 
 /// 读取SDK候选者缓存
-pub fn read_sdk_candidates_cache() -> Result<Option<Vec<Sdk>>> {
+pub fn read_sdk_candidates_cache() -> Result<Option<Cached<Vec<Sdk>>>> {
     let cache_dir = get_cache_dir()?;
     let cache_file = cache_dir.join(SDK_CANDIDATES_CACHE);
 
@@ -103,21 +199,45 @@ pub fn read_sdk_candidates_cache() -> Result<Option<Vec<Sdk>>> {
     let content = fs::read_to_string(&cache_file)
         .context("Failed to read SDK candidates cache")?;
 
-    let candidates: Vec<Sdk> = serde_json::from_str(&content)
+    parse_candidates_cache(&content).map(Some)
+}
+
+fn parse_candidates_cache(content: &str) -> Result<Cached<Vec<Sdk>>> {
+    let stored: StoredCandidates = serde_json::from_str(content)
         .context("Failed to parse SDK candidates cache")?;
 
-    Ok(Some(candidates))
+    Ok(match stored {
+        StoredCandidates::Current(cache_data) => Cached {
+            data: cache_data.candidates,
+            timestamp: cache_data.timestamp,
+            validators: cache_data.validators,
+        },
+        // 旧格式没有时间戳，视为已过期
+        StoredCandidates::Legacy(candidates) => Cached {
+            data: candidates,
+            timestamp: 0,
+            validators: Validators::default(),
+        },
+    })
 }
 
 /// 缓存SDK版本列表（通用，支持所有candidate）
-pub fn cache_sdk_versions(candidate: &str, versions: &[SdkVersion]) -> Result<()> {
+pub fn cache_sdk_versions(candidate: &str, versions: &[SdkVersion], validators: &Validators) -> Result<()> {
     let cache_dir = get_cache_dir()?;
-    let cache_file = cache_dir.join(format!("{}_versions.json", candidate));
+    let platform = detect_platform();
+    let cache_file = versions_cache_path(&cache_dir, candidate, &platform);
+
+    if let Some(parent) = cache_file.parent() {
+        fs::create_dir_all(parent)
+            .context("Failed to create versions cache directory")?;
+    }
 
     let cache_data = SdkVersionsCache {
         candidate: candidate.to_string(),
+        platform,
         versions: versions.to_vec(),
         timestamp: get_timestamp(),
+        validators: validators.clone(),
     };
 
     let json = serde_json::to_string_pretty(&cache_data)
@@ -130,9 +250,10 @@ pub fn cache_sdk_versions(candidate: &str, versions: &[SdkVersion]) -> Result<()
 }
 
 /// 读取SDK版本缓存（通用，支持所有candidate）
-pub fn read_sdk_versions_cache(candidate: &str) -> Result<Option<Vec<SdkVersion>>> {
+pub fn read_sdk_versions_cache(candidate: &str) -> Result<Option<Cached<Vec<SdkVersion>>>> {
     let cache_dir = get_cache_dir()?;
-    let cache_file = cache_dir.join(format!("{}_versions.json", candidate));
+    let platform = detect_platform();
+    let cache_file = versions_cache_path(&cache_dir, candidate, &platform);
 
     if !cache_file.exists() {
         return Ok(None);
@@ -144,7 +265,89 @@ pub fn read_sdk_versions_cache(candidate: &str) -> Result<Option<Vec<SdkVersion>
     let cache_data: SdkVersionsCache = serde_json::from_str(&content)
         .context(format!("Failed to parse {} versions cache", candidate))?;
 
-    Ok(Some(cache_data.versions))
+    // 文件被复制或同步到其他平台时不使用
+    if cache_data.platform != platform {
+        println!("Ignoring {} versions cache for platform {}", candidate, cache_data.platform);
+        return Ok(None);
+    }
+
+    Ok(Some(Cached {
+        data: cache_data.versions,
+        timestamp: cache_data.timestamp,
+        validators: cache_data.validators,
+    }))
+}
+
+/// 缓存版本目录大小
+pub fn cache_disk_usage(index: &SizeIndex) -> Result<()> {
+    let cache_file = get_cache_dir()?.join(DISK_USAGE_CACHE);
+
+    let json = serde_json::to_string(index)
+        .context("Failed to serialize disk usage cache")?;
+
+    fs::write(&cache_file, json)
+        .context("Failed to write disk usage cache")?;
+
+    Ok(())
+}
+
+/// 读取版本目录大小缓存（不存在时为空）
+pub fn read_disk_usage_cache() -> Result<SizeIndex> {
+    let cache_file = get_cache_dir()?.join(DISK_USAGE_CACHE);
+
+    if !cache_file.exists() {
+        return Ok(SizeIndex::default());
+    }
+
+    let content = fs::read_to_string(&cache_file)
+        .context("Failed to read disk usage cache")?;
+
+    serde_json::from_str(&content)
+        .context("Failed to parse disk usage cache")
+}
+
+/// 获取缓存状态（缓存不存在时返回 `None`）
+pub fn cache_info(kind: CacheKind, candidate: Option<&str>, config: &AppConfig) -> Result<Option<CacheInfo>> {
+    let timestamp = match (kind, candidate) {
+        (CacheKind::Candidates, _) => read_sdk_candidates_cache()?.map(|c| c.timestamp),
+        (CacheKind::Versions, Some(candidate)) => read_sdk_versions_cache(candidate)?.map(|c| c.timestamp),
+        (CacheKind::Versions, None) => None,
+    };
+
+    let ttl_secs = kind.ttl_secs(config);
+    Ok(timestamp.map(|fetched_at| CacheInfo {
+        kind,
+        candidate: candidate.map(str::to_string),
+        fetched_at,
+        ttl_secs,
+        stale: is_stale_at(fetched_at, ttl_secs, get_timestamp()),
+        offline: false,
+    }))
+}
+
+/// 删除旧版本未区分平台的版本列表缓存，返回删除的文件数
+///
+/// 旧缓存无法确定对应的平台，直接丢弃，下次访问时重新获取
+pub fn discard_legacy_caches() -> Result<usize> {
+    discard_legacy_in(&get_cache_dir()?)
+}
+
+fn discard_legacy_in(cache_dir: &Path) -> Result<usize> {
+    let mut removed = 0;
+
+    for entry in fs::read_dir(cache_dir).context("Failed to read cache directory")? {
+        let entry = entry?;
+        let name = entry.file_name();
+        let is_legacy = name.to_str().is_some_and(|n| n.ends_with(LEGACY_VERSIONS_SUFFIX));
+
+        if is_legacy && entry.file_type()?.is_file() {
+            fs::remove_file(entry.path())
+                .with_context(|| format!("Failed to remove legacy cache {}", entry.path().display()))?;
+            removed += 1;
+        }
+    }
+
+    Ok(removed)
 }
 
 /// 清除所有缓存
@@ -162,3 +365,74 @@ pub fn clear_all_cache() -> Result<()> {
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_staleness() {
+        assert!(!is_stale_at(1_000, 3600, 1_000 + 3599));
+        assert!(is_stale_at(1_000, 3600, 1_000 + 3600));
+        // 有效期为 0 时总是过期
+        assert!(is_stale_at(1_000, 0, 1_000));
+        // 时钟回拨不视为过期
+        assert!(!is_stale_at(2_000, 3600, 1_000));
+    }
+
+    #[test]
+    fn test_versions_cache_keyed_by_platform() {
+        let dir = Path::new("/cache");
+        assert_eq!(
+            versions_cache_path(dir, "java", "linuxx64"),
+            Path::new("/cache/versions/linuxx64/java.json")
+        );
+        assert_ne!(
+            versions_cache_path(dir, "java", "linuxx64"),
+            versions_cache_path(dir, "java", "darwinarm64")
+        );
+    }
+
+    #[test]
+    fn test_discard_legacy_caches() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("jdk_versions.json"), "{}").unwrap();
+        fs::write(dir.path().join("gradle_versions.json"), "{}").unwrap();
+        fs::write(dir.path().join(SDK_CANDIDATES_CACHE), "[]").unwrap();
+        let current = versions_cache_path(dir.path(), "java", "linuxx64");
+        fs::create_dir_all(current.parent().unwrap()).unwrap();
+        fs::write(&current, "{}").unwrap();
+
+        assert_eq!(discard_legacy_in(dir.path()).unwrap(), 2);
+        assert!(!dir.path().join("jdk_versions.json").exists());
+        assert!(dir.path().join(SDK_CANDIDATES_CACHE).exists());
+        assert!(current.exists());
+        assert_eq!(discard_legacy_in(dir.path()).unwrap(), 0);
+    }
+
+    #[test]
+    fn test_legacy_candidates_cache_is_stale() {
+        let sdk = Sdk {
+            candidate: "gradle".to_string(),
+            name: "Gradle".to_string(),
+            description: String::new(),
+            website: None,
+            latest_version: None,
+            installed_version: None,
+            installed: false,
+            category: crate::models::Category::BuildTools,
+        };
+        let legacy = serde_json::to_string(&vec![sdk]).unwrap();
+        let cached = parse_candidates_cache(&legacy).unwrap();
+        assert_eq!(cached.data.len(), 1);
+        assert_eq!(cached.timestamp, 0);
+        assert!(cached.is_stale(3600));
+
+        let current = serde_json::to_string(&SdkCandidatesCache {
+            candidates: cached.data,
+            timestamp: get_timestamp(),
+            validators: Validators::default(),
+        }).unwrap();
+        assert!(!parse_candidates_cache(&current).unwrap().is_stale(3600));
+    }
+}
@@ -1,255 +1,608 @@
-use tauri::{State, AppHandle};
+use tauri::{State, AppHandle, Emitter, Manager};
 use tokio::sync::Mutex;
-use std::sync::Arc;
-use crate::api::SdkmanApiClient;
-use crate::models::{SdkVersion, Sdk, Statistics};
-use crate::local::{LocalScanner, Downloader, Installer, SymlinkManager};
+use tokio_util::sync::CancellationToken;
+use std::collections::HashSet;
+use std::future::Future;
+use std::sync::{Arc, OnceLock};
+use crate::api::{Fetched, SdkmanApiClient};
+use crate::api::endpoints::{self, ApiEndpoints, EndpointUsed, ServedBy};
+use crate::api::http;
+use crate::api::probe::{self, ProbeResult};
+use crate::models::{DiskUsage, SdkVersion, Sdk, Statistics};
+use crate::local::{InstallKind, LocalScanner, Downloader, Installer, SymlinkManager};
+use crate::local::checksum::{self, ChecksumStatus};
+use crate::local::paths;
+use crate::local::usage;
+use crate::local::watcher::InstalledWatcher;
 use crate::config::AppConfig;
-use crate::cache;
-use std::path::Path;
+use crate::utils::version;
+use crate::error::{CommandError, ErrorCode};
+use crate::cache::{self, CacheInfo, CacheKind, CacheUpdated, Cached, Validators};
+use crate::jobs::{self, Cancelled, JobCancelled, JobInfo, JobKind, JobManager, JobResult, JobStarted};
+use crate::network::{NetworkState, NetworkStatus, Offline, OfflineReason};
+use std::path::PathBuf;
 
 #[tauri::command]
 pub async fn list_jdk_versions(
     force_refresh: bool,
+    app: AppHandle,
     client: State<'_, Arc<Mutex<SdkmanApiClient>>>
-) -> Result<Vec<SdkVersion>, String> {
-    // 1. 如果不强制刷新，先尝试读取缓存
-    let mut versions = if !force_refresh {
-        if let Ok(Some(cached)) = cache::read_jdk_versions_cache() {
-            println!("Using cached JDK versions ({} items)", cached.len());
-            cached
-        } else {
-            println!("No cache found, fetching from API");
-            fetch_and_cache_jdk_versions(&client).await?
-        }
-    } else {
-        println!("Force refresh, fetching from API");
-        fetch_and_cache_jdk_versions(&client).await?
-    };
+) -> Result<Vec<SdkVersion>, CommandError> {
+    // 1. 读取版本列表（缓存过期时先返回旧数据并在后台刷新，离线时只使用缓存）
+    let mut versions = load_versions("java", force_refresh, &app, &client).await?;
 
-    // 2. 扫描本地已安装版本
-    let installed_versions = LocalScanner::scan_installed_versions("java")
-        .map_err(|e| e.to_string())?;
+    // 2. 标记本地安装状态，合并只存在于本地的版本
+    merge_installed("java", &mut versions)?;
 
-    // 3. 获取当前版本
-    let current_version = LocalScanner::get_current_version("java")
-        .map_err(|e| e.to_string())?;
+    Ok(versions)
+}
 
-    // 4. 标记已安装和当前版本
-    for version in &mut versions {
-        version.installed = installed_versions.contains(&version.identifier);
+/// 按本地扫描结果标记已安装和当前版本、已安装版本的校验结果，
+/// 并合并远程列表中没有的本地版本（本地链接、损坏的链接、已下线的版本）
+fn merge_installed(candidate: &str, versions: &mut Vec<SdkVersion>) -> Result<(), CommandError> {
+    let installed = LocalScanner::scan_installed(candidate)?;
+    let current_version = LocalScanner::get_current_version(candidate)?;
+
+    let local_only: Vec<SdkVersion> = installed
+        .iter()
+        .filter(|entry| !versions.iter().any(|v| v.identifier == entry.version))
+        .map(|entry| SdkVersion::local(candidate, &entry.version))
+        .collect();
+    if !local_only.is_empty() {
+        versions.extend(local_only);
+        version::sort_descending(versions, |v| v.identifier.as_str());
+    }
+
+    let checksum_records = checksum::read_records().unwrap_or_default();
+    for version in versions.iter_mut() {
+        let entry = installed.iter().find(|entry| entry.version == version.identifier);
+        version.installed = entry.is_some_and(|entry| entry.kind != InstallKind::Broken);
+        version.install_kind = entry.map(|entry| entry.kind);
+        version.link_target = entry
+            .and_then(|entry| entry.target.as_ref())
+            .map(|target| target.to_string_lossy().to_string());
         version.in_use = current_version.as_ref() == Some(&version.identifier);
         version.is_default = version.in_use;
+        version.checksum = if version.installed {
+            checksum::get_status(&checksum_records, &version.candidate, &version.identifier)
+        } else {
+            None
+        };
     }
 
-    Ok(versions)
+    Ok(())
 }
 
-/// 从API获取JDK版本并缓存
-async fn fetch_and_cache_jdk_versions(
-    client: &State<'_, Arc<Mutex<SdkmanApiClient>>>
-) -> Result<Vec<SdkVersion>, String> {
-    let client_guard = client.lock().await;
-    let versions = client_guard.list_versions("java")
-        .await
-        .map_err(|e| e.to_string())?;
-    drop(client_guard);
-
-    // 缓存结果
-    if let Err(e) = cache::cache_jdk_versions(&versions) {
-        eprintln!("Warning: Failed to cache JDK versions: {}", e);
-    }
-
-    Ok(versions)
+/// 通知前端实际提供数据的端点（主地址或镜像）
+fn emit_endpoint_used(app: &AppHandle, request: &str, candidate: Option<&str>, served_by: &ServedBy) {
+    let _ = app.emit("api-endpoint", &EndpointUsed::new(request, candidate, served_by));
 }
 
 #[tauri::command]
 pub async fn list_sdk_candidates(
     force_refresh: bool,
+    app: AppHandle,
     client: State<'_, Arc<Mutex<SdkmanApiClient>>>
-) -> Result<Vec<Sdk>, String> {
-    // 如果不强制刷新，先尝试读取缓存
-    if !force_refresh {
-        if let Ok(Some(cached)) = cache::read_sdk_candidates_cache() {
-            println!("Using cached SDK candidates ({} items)", cached.len());
-            return Ok(cached);
-        }
-    }
+) -> Result<Vec<Sdk>, CommandError> {
+    load_candidates(force_refresh, &app, &client).await
+}
+
+/// 读取候选者列表（缓存策略同 `load_versions`）
+async fn load_candidates(
+    force_refresh: bool,
+    app: &AppHandle,
+    client: &Arc<Mutex<SdkmanApiClient>>
+) -> Result<Vec<Sdk>, CommandError> {
+    let cached = cache::read_sdk_candidates_cache().ok().flatten();
+    let fetch = fetch_and_cache_candidates(app, client);
+    load_list(CacheKind::Candidates, None, force_refresh, cached, fetch, app, client).await
+}
+
+/// 从API获取SDK候选者并缓存（已有缓存时发送条件请求）
+async fn fetch_and_cache_candidates(
+    app: &AppHandle,
+    client: &Arc<Mutex<SdkmanApiClient>>
+) -> anyhow::Result<Vec<Sdk>> {
+    let cached = cache::read_sdk_candidates_cache().ok().flatten();
 
-    println!("Fetching SDK candidates from API");
     let client_guard = client.lock().await;
-    let candidates = client_guard.list_candidates()
-        .await
-        .map_err(|e| e.to_string())?;
+    let result = client_guard.list_candidates_if_modified(cached.as_ref().map(|c| &c.validators)).await;
     drop(client_guard);
+    let (fetched, served_by) = track_connectivity(app, result)?;
+
+    emit_endpoint_used(app, "candidates", None, &served_by);
 
-    // 缓存结果
-    if let Err(e) = cache::cache_sdk_candidates(&candidates) {
+    let (candidates, validators) = resolve_fetched(fetched, cached, "SDK candidates")?;
+    offline_caches().lock().unwrap().remove(&cache::cache_key(CacheKind::Candidates, None));
+
+    // 缓存结果（内容未变化时也更新时间戳）
+    if let Err(e) = cache::cache_sdk_candidates(&candidates, &validators) {
         eprintln!("Warning: Failed to cache SDK candidates: {}", e);
     }
 
     Ok(candidates)
 }
 
+/// 合并条件请求结果和已有缓存：304 时沿用缓存数据
+fn resolve_fetched<T>(
+    fetched: Fetched<Vec<T>>,
+    cached: Option<Cached<Vec<T>>>,
+    what: &str,
+) -> anyhow::Result<(Vec<T>, Validators)> {
+    match (fetched, cached) {
+        (Fetched::Modified(data, validators), _) => Ok((data, validators)),
+        (Fetched::NotModified, Some(cached)) => {
+            println!("{} not modified, keeping cached data", what);
+            Ok((cached.data, cached.validators))
+        }
+        (Fetched::NotModified, None) => anyhow::bail!("Unexpected 304 response for {}", what),
+    }
+}
+
+/// 记录请求结果对应的网络状态：连接失败累计到阈值时进入自动离线模式，成功时恢复
+fn track_connectivity<T>(app: &AppHandle, result: anyhow::Result<T>) -> anyhow::Result<T> {
+    let network = app.state::<NetworkState>();
+    let changed = match &result {
+        Ok(_) => network.record_success(),
+        Err(e) if endpoints::is_unreachable(e) => network.record_failure(),
+        Err(_) => false,
+    };
+
+    if changed {
+        emit_network_status(app);
+    }
+    result
+}
+
+/// 通知前端网络状态变化
+fn emit_network_status(app: &AppHandle) {
+    let config = AppConfig::load().unwrap_or_default();
+    let status = app.state::<NetworkState>().status(&config);
+    match status.reason {
+        Some(reason) => println!("Network status: offline ({:?})", reason),
+        None => println!("Network status: online"),
+    }
+    let _ = app.emit("network-status", &status);
+}
+
+/// 离线时阻止需要网络的操作
+fn ensure_online(app: &AppHandle, operation: impl Into<String>) -> Result<(), CommandError> {
+    let config = AppConfig::load().unwrap_or_default();
+    if app.state::<NetworkState>().status(&config).offline {
+        return Err(Offline::new(operation).into());
+    }
+    Ok(())
+}
+
 /// 获取指定SDK的版本列表（通用版本，支持所有candidate）
 #[tauri::command]
 pub async fn list_sdk_versions(
     candidate: String,
     force_refresh: bool,
+    app: AppHandle,
     client: State<'_, Arc<Mutex<SdkmanApiClient>>>
-) -> Result<Vec<SdkVersion>, String> {
-    // 1. 如果不强制刷新，先尝试读取缓存
-    let mut versions = if !force_refresh {
-        if let Ok(Some(cached)) = cache::read_sdk_versions_cache(&candidate) {
-            println!("Using cached {} versions ({} items)", candidate, cached.len());
-            cached
-        } else {
-            println!("No cache found for {}, fetching from API", candidate);
-            fetch_and_cache_sdk_versions(&candidate, &client).await?
-        }
-    } else {
-        println!("Force refresh for {}, fetching from API", candidate);
-        fetch_and_cache_sdk_versions(&candidate, &client).await?
-    };
+) -> Result<Vec<SdkVersion>, CommandError> {
+    // 1. 读取版本列表（缓存过期时先返回旧数据并在后台刷新，离线时只使用缓存）
+    let mut versions = load_versions(&candidate, force_refresh, &app, &client).await?;
 
-    // 2. 扫描本地已安装版本
-    let installed_versions = LocalScanner::scan_installed_versions(&candidate)
-        .map_err(|e| e.to_string())?;
+    // 2. 标记本地安装状态，合并只存在于本地的版本
+    merge_installed(&candidate, &mut versions)?;
 
-    // 3. 获取当前版本
-    let current_version = LocalScanner::get_current_version(&candidate)
-        .map_err(|e| e.to_string())?;
+    Ok(versions)
+}
 
-    // 4. 标记已安装和当前版本
-    for version in &mut versions {
-        version.installed = installed_versions.contains(&version.identifier);
-        version.in_use = current_version.as_ref() == Some(&version.identifier);
-        version.is_default = version.in_use;
+/// 读取版本列表：缓存有效时直接返回；已过期时返回旧数据并在后台刷新；没有缓存时从API获取
+async fn load_versions(
+    candidate: &str,
+    force_refresh: bool,
+    app: &AppHandle,
+    client: &Arc<Mutex<SdkmanApiClient>>
+) -> Result<Vec<SdkVersion>, CommandError> {
+    let cached = cache::read_sdk_versions_cache(candidate).ok().flatten();
+    let fetch = fetch_and_cache_sdk_versions(candidate, app, client);
+    load_list(CacheKind::Versions, Some(candidate), force_refresh, cached, fetch, app, client).await
+}
+
+/// 按缓存策略和网络状态读取列表
+///
+/// - 手动离线：只使用缓存
+/// - 自动离线：使用缓存，手动刷新时仍尝试连接
+/// - 在线：缓存有效时直接返回，过期时返回旧数据并在后台刷新；
+///   请求因网络不可达失败时回退到缓存
+async fn load_list<T>(
+    kind: CacheKind,
+    candidate: Option<&str>,
+    force_refresh: bool,
+    cached: Option<Cached<Vec<T>>>,
+    fetch: impl Future<Output = anyhow::Result<Vec<T>>>,
+    app: &AppHandle,
+    client: &Arc<Mutex<SdkmanApiClient>>,
+) -> Result<Vec<T>, CommandError> {
+    let what = match candidate {
+        Some(candidate) => format!("{} versions", candidate),
+        None => "SDK candidates".to_string(),
+    };
+    let config = AppConfig::load().unwrap_or_default();
+    let try_network = match app.state::<NetworkState>().status(&config).reason {
+        Some(OfflineReason::Manual) => false,
+        Some(OfflineReason::Auto) => force_refresh,
+        None => true,
+    };
+
+    if !try_network || (!force_refresh && cached.is_some()) {
+        let Some(cached) = cached else {
+            return Err(Offline::new(format!("Loading {} without a cache", what)).into());
+        };
+
+        if !try_network {
+            println!("Offline, using cached {} ({} items)", what, cached.data.len());
+            mark_served_from_cache(app, kind, candidate, &config);
+        } else if cached.is_stale(kind.ttl_secs(&config)) {
+            println!("Using stale {} ({} items), refreshing in background", what, cached.data.len());
+            refresh_in_background(app, client, kind, candidate);
+        } else {
+            println!("Using cached {} ({} items)", what, cached.data.len());
+        }
+        return Ok(cached.data);
     }
 
-    Ok(versions)
+    println!("Fetching {} from API", what);
+    match fetch.await {
+        Ok(data) => Ok(data),
+        Err(e) if endpoints::is_unreachable(&e) && cached.is_some() => {
+            eprintln!("Warning: Failed to fetch {}, using cached data: {:#}", what, e);
+            mark_served_from_cache(app, kind, candidate, &config);
+            Ok(cached.map(|c| c.data).unwrap_or_default())
+        }
+        Err(e) => Err(e.into()),
+    }
 }
 
-/// 从API获取SDK版本并缓存
+/// 从API获取SDK版本并缓存（已有缓存时发送条件请求）
 async fn fetch_and_cache_sdk_versions(
     candidate: &str,
-    client: &State<'_, Arc<Mutex<SdkmanApiClient>>>
-) -> Result<Vec<SdkVersion>, String> {
+    app: &AppHandle,
+    client: &Arc<Mutex<SdkmanApiClient>>
+) -> anyhow::Result<Vec<SdkVersion>> {
+    let cached = cache::read_sdk_versions_cache(candidate).ok().flatten();
+
     let client_guard = client.lock().await;
-    let versions = client_guard.list_versions(candidate)
-        .await
-        .map_err(|e| e.to_string())?;
+    let result = client_guard.list_versions_if_modified(candidate, cached.as_ref().map(|c| &c.validators)).await;
     drop(client_guard);
+    let (fetched, served_by) = track_connectivity(app, result)?;
 
-    // 缓存结果
-    if let Err(e) = cache::cache_sdk_versions(candidate, &versions) {
+    emit_endpoint_used(app, "versions", Some(candidate), &served_by);
+
+    let (versions, validators) = resolve_fetched(fetched, cached, &format!("{} versions", candidate))?;
+    offline_caches().lock().unwrap().remove(&cache::cache_key(CacheKind::Versions, Some(candidate)));
+
+    // 缓存结果（内容未变化时也更新时间戳）
+    if let Err(e) = cache::cache_sdk_versions(candidate, &versions, &validators) {
         eprintln!("Warning: Failed to cache {} versions: {}", candidate, e);
     }
 
     Ok(versions)
 }
 
+/// 正在后台刷新的缓存，避免同一列表重复刷新
+fn refreshing_caches() -> &'static std::sync::Mutex<HashSet<String>> {
+    static REFRESHING: OnceLock<std::sync::Mutex<HashSet<String>>> = OnceLock::new();
+    REFRESHING.get_or_init(Default::default)
+}
+
+/// 获取失败、正在显示缓存数据的列表，成功获取后移除
+fn offline_caches() -> &'static std::sync::Mutex<HashSet<String>> {
+    static OFFLINE: OnceLock<std::sync::Mutex<HashSet<String>>> = OnceLock::new();
+    OFFLINE.get_or_init(Default::default)
+}
+
+/// 标记列表正在显示缓存数据，并发送 `cache-stale` 事件供界面标注
+fn mark_served_from_cache(app: &AppHandle, kind: CacheKind, candidate: Option<&str>, config: &AppConfig) {
+    offline_caches().lock().unwrap().insert(cache::cache_key(kind, candidate));
+    match list_cache_info(kind, candidate, config) {
+        Ok(Some(info)) => {
+            let _ = app.emit("cache-stale", &info);
+        }
+        Ok(None) => {}
+        Err(e) => eprintln!("Warning: Failed to read cache info: {}", e),
+    }
+}
+
+/// 列表缓存状态，包含最近一次获取是否失败
+fn list_cache_info(kind: CacheKind, candidate: Option<&str>, config: &AppConfig) -> anyhow::Result<Option<CacheInfo>> {
+    let offline = offline_caches().lock().unwrap().contains(&cache::cache_key(kind, candidate));
+    Ok(cache::cache_info(kind, candidate, config)?.map(|info| CacheInfo { offline, ..info }))
+}
+
+/// 在后台刷新过期缓存，完成后发送 `cache-updated` 事件
+fn refresh_in_background(
+    app: &AppHandle,
+    client: &Arc<Mutex<SdkmanApiClient>>,
+    kind: CacheKind,
+    candidate: Option<&str>,
+) {
+    let key = cache::cache_key(kind, candidate);
+    if !refreshing_caches().lock().unwrap().insert(key.clone()) {
+        return;
+    }
+
+    let app = app.clone();
+    let client = client.clone();
+    let candidate = candidate.map(str::to_string);
+
+    tauri::async_runtime::spawn(async move {
+        let result = match &candidate {
+            Some(candidate) => fetch_and_cache_sdk_versions(candidate, &app, &client).await.map(|_| ()),
+            None => fetch_and_cache_candidates(&app, &client).await.map(|_| ()),
+        };
+        refreshing_caches().lock().unwrap().remove(&key);
+
+        match result {
+            Ok(()) => {
+                println!("Background refresh of {} completed", key);
+                let _ = app.emit("cache-updated", &CacheUpdated::new(kind, candidate.as_deref()));
+            }
+            Err(e) => {
+                eprintln!("Warning: Background refresh of {} failed: {}", key, e);
+                if endpoints::is_unreachable(&e) {
+                    let config = AppConfig::load().unwrap_or_default();
+                    mark_served_from_cache(&app, kind, candidate.as_deref(), &config);
+                }
+            }
+        }
+    });
+}
+
+/// 获取列表缓存状态（最后获取时间、是否过期、是否因获取失败显示缓存数据）
+#[tauri::command]
+pub async fn get_cache_info(kind: CacheKind, candidate: Option<String>) -> Result<Option<CacheInfo>, CommandError> {
+    let config = AppConfig::load().unwrap_or_default();
+    list_cache_info(kind, candidate.as_deref(), &config)
+        .map_err(CommandError::from)
+}
+
+/// 获取 SDKMAN 目录的磁盘占用（按候选者和版本统计）
+///
+/// 未变化的版本目录使用缓存的大小；`force_refresh` 为 true 时重新统计全部目录
+#[tauri::command]
+pub async fn get_disk_usage(force_refresh: bool) -> Result<DiskUsage, CommandError> {
+    let usage = run_blocking(move || usage::disk_usage(force_refresh)).await?;
+    Ok(usage)
+}
+
+/// 在阻塞线程池中执行文件系统操作（统计目录、等待 SDKMAN 目录锁等），不占用异步运行时的工作线程
+async fn run_blocking<T: Send + 'static>(
+    task: impl FnOnce() -> anyhow::Result<T> + Send + 'static,
+) -> anyhow::Result<T> {
+    tauri::async_runtime::spawn_blocking(task)
+        .await
+        .map_err(anyhow::Error::from)?
+}
+
+/// 获取统计信息
+///
+/// 可用数量遵循列表的缓存策略；离线且没有缓存时可用数量为 0，并标记为过期
 #[tauri::command]
 pub async fn get_statistics(
+    app: AppHandle,
     client: State<'_, Arc<Mutex<SdkmanApiClient>>>
-) -> Result<Statistics, String> {
+) -> Result<Statistics, CommandError> {
     // 1. 获取已安装的JDK数量
-    let jdk_installed = LocalScanner::scan_installed_versions("java")
-        .map_err(|e| e.to_string())?
+    let jdk_installed = LocalScanner::scan_installed_versions("java")?
         .len();
 
-    // 2. 获取可用的JDK数量
-    let client_guard = client.lock().await;
-    let jdk_versions = client_guard.list_versions("java")
-        .await
-        .map_err(|e| e.to_string())?;
-    let jdk_available = jdk_versions.len();
+    // 2. 获取可用的JDK和候选者数量
+    let jdk_versions = load_versions("java", false, &app, &client).await;
+    let candidates = load_candidates(false, &app, &client).await;
 
-    // 3. 获取所有候选者
-    let candidates = client_guard.list_candidates()
-        .await
-        .map_err(|e| e.to_string())?;
+    let config = AppConfig::load().unwrap_or_default();
+    let status = app.state::<NetworkState>().status(&config);
 
-    // 释放锁，以便后续操作可以使用
-    drop(client_guard);
+    // 在线时列表获取失败直接返回错误
+    if !status.offline {
+        if let Err(e) = jdk_versions.as_ref().and(candidates.as_ref()) {
+            return Err(e.clone());
+        }
+    }
 
-    let sdk_available = candidates.len();
+    let jdk_available = jdk_versions.map(|v| v.len()).unwrap_or(0);
+    let sdk_available = candidates.map(|c| c.len()).unwrap_or(0);
 
-    // 4. 获取已安装的SDK总数（所有候选者的已安装版本数）
+    // 3. 获取已安装的SDK总数（所有已安装候选者的版本数，仅扫描本地）
     let mut sdk_installed = 0usize;
-    for candidate in &candidates {
-        if let Ok(versions) = LocalScanner::scan_installed_versions(&candidate.candidate) {
+    for candidate in LocalScanner::list_installed_candidates().unwrap_or_default() {
+        if let Ok(versions) = LocalScanner::scan_installed_versions(&candidate) {
             sdk_installed += versions.len();
         }
     }
 
+    // 4. 任一列表缓存过期或不存在时标记为过期
+    let stale = [(CacheKind::Versions, Some("java")), (CacheKind::Candidates, None)]
+        .into_iter()
+        .any(|(kind, candidate)| {
+            !matches!(cache::cache_info(kind, candidate, &config), Ok(Some(info)) if !info.stale)
+        });
+
     Ok(Statistics {
         jdk_installed,
         jdk_available,
         sdk_installed,
         sdk_available,
+        offline: status.offline,
+        stale,
     })
 }
 
+/// 获取当前网络状态
+#[tauri::command]
+pub async fn get_network_status(app: AppHandle) -> Result<NetworkStatus, CommandError> {
+    let config = AppConfig::load()?;
+    Ok(app.state::<NetworkState>().status(&config))
+}
+
+/// 检查 API 是否可达，并据此更新自动离线状态
+#[tauri::command]
+pub async fn check_connectivity(app: AppHandle) -> Result<NetworkStatus, CommandError> {
+    let config = AppConfig::load()?;
+
+    let endpoint = ApiEndpoints::api_list(&config).remove(0);
+    let result = probe::probe_with_config(&config, &endpoint.candidates_list())
+        .await?;
+
+    let network = app.state::<NetworkState>();
+    let changed = if result.success {
+        network.record_success()
+    } else {
+        println!("Connectivity check failed: {:?}", result.failure);
+        network.record_failure()
+    };
+    if changed {
+        emit_network_status(&app);
+    }
+
+    Ok(network.status(&config))
+}
+
 /// 扫描指定候选者的已安装版本
 #[tauri::command]
-pub async fn scan_installed_sdks(candidate: String) -> Result<Vec<String>, String> {
+pub async fn scan_installed_sdks(candidate: String) -> Result<Vec<String>, CommandError> {
     LocalScanner::scan_installed_versions(&candidate)
-        .map_err(|e| e.to_string())
+        .map_err(CommandError::from)
 }
 
 /// 获取当前默认版本
 #[tauri::command]
-pub async fn get_current_sdk_version(candidate: String) -> Result<Option<String>, String> {
+pub async fn get_current_sdk_version(candidate: String) -> Result<Option<String>, CommandError> {
     LocalScanner::get_current_version(&candidate)
-        .map_err(|e| e.to_string())
+        .map_err(CommandError::from)
 }
 
 /// 获取所有已安装的候选者列表
 #[tauri::command]
-pub async fn list_installed_candidates() -> Result<Vec<String>, String> {
+pub async fn list_installed_candidates() -> Result<Vec<String>, CommandError> {
     LocalScanner::list_installed_candidates()
-        .map_err(|e| e.to_string())
+        .map_err(CommandError::from)
 }
 
 /// 检查指定版本是否已安装
 #[tauri::command]
-pub async fn is_sdk_installed(candidate: String, version: String) -> Result<bool, String> {
+pub async fn is_sdk_installed(candidate: String, version: String) -> Result<bool, CommandError> {
     LocalScanner::is_version_installed(&candidate, &version)
-        .map_err(|e| e.to_string())
+        .map_err(CommandError::from)
 }
 
-/// 下载SDK（带进度报告）
-#[tauri::command]
-pub async fn download_sdk(
-    candidate: String,
-    version: String,
-    app: AppHandle,
-) -> Result<String, String> {
-    let downloader = Downloader::new()
-        .map_err(|e| e.to_string())?;
+/// 注册任务并通知前端任务ID；同一版本已有任务在排队或执行时返回错误
+fn start_job(
+    jobs: &JobManager,
+    app: &AppHandle,
+    kind: JobKind,
+    candidate: &str,
+    version: &str,
+) -> Result<(String, CancellationToken), CommandError> {
+    let (job_id, token) = jobs.start(kind, candidate, version)?;
+
+    let started = JobStarted {
+        job_id: job_id.clone(),
+        kind,
+        candidate: candidate.to_string(),
+        version: version.to_string(),
+    };
+    let _ = app.emit("job-started", &started);
+    emit_job_queue(app, jobs);
 
-    let temp_path = downloader.download_sdk(&candidate, &version, app)
-        .await
-        .map_err(|e| e.to_string())?;
+    Ok((job_id, token))
+}
+
+/// 通知前端任务队列变化（排队位置、开始执行、结束）
+fn emit_job_queue(app: &AppHandle, jobs: &JobManager) {
+    let _ = app.emit("job-queue", &jobs.list());
+}
 
-    Ok(temp_path.to_string_lossy().to_string())
+/// 等待空闲槽位后执行任务，排队期间可以取消
+async fn run_queued<T>(
+    jobs: &JobManager,
+    app: &AppHandle,
+    job_id: &str,
+    token: &CancellationToken,
+    task: impl Future<Output = anyhow::Result<T>>,
+) -> anyhow::Result<T> {
+    let _slot = jobs.wait_for_slot(job_id, token).await?;
+    emit_job_queue(app, jobs);
+    task.await
+}
+
+/// 结束任务；如果任务被取消，发送 job-cancelled 事件
+fn finish_job<T>(
+    jobs: &JobManager,
+    app: &AppHandle,
+    job_id: &str,
+    candidate: &str,
+    version: &str,
+    result: anyhow::Result<T>,
+) -> Result<T, CommandError> {
+    jobs.finish(job_id);
+    emit_job_queue(app, jobs);
+
+    match result {
+        Ok(value) => Ok(value),
+        Err(e) => {
+            if jobs::is_cancelled(&e) {
+                let cancelled = JobCancelled {
+                    job_id: job_id.to_string(),
+                    candidate: candidate.to_string(),
+                    version: version.to_string(),
+                };
+                let _ = app.emit("job-cancelled", &cancelled);
+            }
+            Err(e.into())
+        }
+    }
 }
 
-/// 下载SDK（简化版，不报告进度）
+/// 获取排队和执行中的下载/安装任务
 #[tauri::command]
-pub async fn download_sdk_simple(
-    candidate: String,
-    version: String,
-) -> Result<String, String> {
-    let downloader = Downloader::new()
-        .map_err(|e| e.to_string())?;
+pub async fn list_jobs(jobs: State<'_, JobManager>) -> Result<Vec<JobInfo>, CommandError> {
+    Ok(jobs.list())
+}
 
-    let temp_path = downloader.download_sdk_simple(&candidate, &version)
-        .await
-        .map_err(|e| e.to_string())?;
+/// 取消正在进行或排队中的下载/安装任务
+#[tauri::command]
+pub async fn cancel_job(
+    job_id: String,
+    jobs: State<'_, JobManager>,
+) -> Result<(), CommandError> {
+    if jobs.cancel(&job_id) {
+        println!("Cancellation requested for job {}", job_id);
+        Ok(())
+    } else {
+        Err(CommandError::new(ErrorCode::JobNotFound, format!("Job {} not found or already finished", job_id))
+            .with_param("job_id", &job_id))
+    }
+}
 
-    Ok(temp_path.to_string_lossy().to_string())
+/// 下载SDK（带进度报告）
+#[tauri::command]
+pub async fn download_sdk(
+    candidate: String,
+    version: String,
+    app: AppHandle,
+    jobs: State<'_, JobManager>,
+) -> Result<JobResult, CommandError> {
+    ensure_online(&app, format!("Downloading {} {}", candidate, version))?;
+
+    let (job_id, token) = start_job(&jobs, &app, JobKind::Download, &candidate, &version)?;
+
+    let result = run_queued(&jobs, &app, &job_id, &token, async {
+        let downloader = Downloader::new()?;
+        downloader.download_sdk(&candidate, &version, app.clone(), &token).await
+    }).await;
+    let archive = finish_job(&jobs, &app, &job_id, &candidate, &version, result)?;
+
+    Ok(JobResult {
+        job_id,
+        path: archive.path.to_string_lossy().to_string(),
+        checksum: Some(archive.checksum),
+    })
 }
 
 /// 从ZIP文件安装SDK
@@ -259,21 +612,36 @@ pub async fn install_sdk(
     candidate: String,
     version: String,
     app: AppHandle,
-) -> Result<String, String> {
-    let zip_path = Path::new(&zip_path);
+    jobs: State<'_, JobManager>,
+) -> Result<JobResult, CommandError> {
+    let (job_id, token) = start_job(&jobs, &app, JobKind::Install, &candidate, &version)?;
+    let result = run_queued(&jobs, &app, &job_id, &token, install_blocking(PathBuf::from(zip_path), &candidate, &version, &app, &token)).await;
+    let install_path = finish_job(&jobs, &app, &job_id, &candidate, &version, result)?;
+
+    // 本地归档没有可校验的来源，清除旧的校验记录
+    if let Err(e) = checksum::remove_status(&candidate, &version) {
+        eprintln!("Warning: Failed to update checksum records: {}", e);
+    }
 
-    let install_path = Installer::install_from_zip(zip_path, &candidate, &version, app)
-        .map_err(|e| e.to_string())?;
+    set_default_if_only_version(&candidate, &version).await?;
 
-    // 如果这是该候选者的唯一已安装版本，自动设置为默认版本
-    let installed_versions = LocalScanner::scan_installed_versions(&candidate)
-        .map_err(|e| e.to_string())?;
+    Ok(JobResult {
+        job_id,
+        path: install_path.to_string_lossy().to_string(),
+        checksum: None,
+    })
+}
+
+/// 如果这是该候选者的唯一已安装版本，自动设置为默认版本
+async fn set_default_if_only_version(candidate: &str, version: &str) -> Result<(), CommandError> {
+    let installed_versions = LocalScanner::scan_installed_versions(candidate)?;
 
     println!("Installed versions for {}: {:?} (count: {})", candidate, installed_versions, installed_versions.len());
 
     if installed_versions.len() == 1 {
         println!("Only one version installed for {}, setting {} as default", candidate, version);
-        if let Err(e) = SymlinkManager::set_default_version(&candidate, &version) {
+        let (c, v) = (candidate.to_string(), version.to_string());
+        if let Err(e) = run_blocking(move || SymlinkManager::set_default_version(&c, &v)).await {
             eprintln!("Warning: Failed to set default version: {}", e);
         } else {
             println!("Successfully set {} {} as default version", candidate, version);
@@ -282,7 +650,7 @@ pub async fn install_sdk(
         println!("Multiple versions ({}) installed, not setting default automatically", installed_versions.len());
     }
 
-    Ok(install_path.to_string_lossy().to_string())
+    Ok(())
 }
 
 /// 卸载SDK
@@ -291,9 +659,21 @@ pub async fn uninstall_sdk(
     candidate: String,
     version: String,
     app: AppHandle,
-) -> Result<(), String> {
-    Installer::uninstall_sdk(&candidate, &version, app)
-        .map_err(|e| e.to_string())
+    jobs: State<'_, JobManager>,
+) -> Result<(), CommandError> {
+    // 正在下载或安装的版本不能卸载，避免删除任务即将替换的目录
+    jobs.ensure_idle(&candidate, &version)?;
+
+    {
+        let (candidate, version) = (candidate.clone(), version.clone());
+        run_blocking(move || Installer::uninstall_sdk(&candidate, &version, app)).await?;
+    }
+
+    if let Err(e) = checksum::remove_status(&candidate, &version) {
+        eprintln!("Warning: Failed to update checksum records: {}", e);
+    }
+
+    Ok(())
 }
 
 /// 验证SDK安装
@@ -301,53 +681,80 @@ pub async fn uninstall_sdk(
 pub async fn verify_sdk_installation(
     candidate: String,
     version: String,
-) -> Result<bool, String> {
+) -> Result<bool, CommandError> {
     Installer::verify_installation(&candidate, &version)
-        .map_err(|e| e.to_string())
+        .map_err(CommandError::from)
 }
 
 /// 下载并安装SDK（组合命令）
+///
+/// 任务开始时通过 `job-started` 事件通知任务ID，可使用 `cancel_job` 取消
 #[tauri::command]
 pub async fn download_and_install_sdk(
     candidate: String,
     version: String,
     app: AppHandle,
-) -> Result<String, String> {
-    // 1. 下载SDK
-    let downloader = Downloader::new()
-        .map_err(|e| e.to_string())?;
-
-    let temp_path = downloader.download_sdk(&candidate, &version, app.clone())
-        .await
-        .map_err(|e| e.to_string())?;
+    jobs: State<'_, JobManager>,
+) -> Result<JobResult, CommandError> {
+    ensure_online(&app, format!("Downloading {} {}", candidate, version))?;
 
-    // 2. 安装SDK
-    let install_path = Installer::install_from_zip(&temp_path, &candidate, &version, app)
-        .map_err(|e| e.to_string())?;
+    let (job_id, token) = start_job(&jobs, &app, JobKind::DownloadAndInstall, &candidate, &version)?;
+    let result = run_queued(&jobs, &app, &job_id, &token, run_download_and_install(&candidate, &version, &app, &token)).await;
+    let (install_path, checksum_status) = finish_job(&jobs, &app, &job_id, &candidate, &version, result)?;
 
-    // 3. 清理临时文件
-    if let Err(e) = std::fs::remove_file(&temp_path) {
-        eprintln!("Warning: Failed to remove temporary file {:?}: {}", temp_path, e);
+    // 记录校验结果，供界面展示
+    if let Err(e) = checksum::record_status(&candidate, &version, &checksum_status) {
+        eprintln!("Warning: Failed to record checksum status: {}", e);
     }
 
-    // 4. 如果这是该候选者的唯一已安装版本，自动设置为默认版本
-    let installed_versions = LocalScanner::scan_installed_versions(&candidate)
-        .map_err(|e| e.to_string())?;
+    // 如果这是该候选者的唯一已安装版本，自动设置为默认版本
+    set_default_if_only_version(&candidate, &version).await?;
 
-    println!("Installed versions for {}: {:?} (count: {})", candidate, installed_versions, installed_versions.len());
+    Ok(JobResult {
+        job_id,
+        path: install_path.to_string_lossy().to_string(),
+        checksum: Some(checksum_status),
+    })
+}
 
-    if installed_versions.len() == 1 {
-        println!("Only one version installed for {}, setting {} as default", candidate, version);
-        if let Err(e) = SymlinkManager::set_default_version(&candidate, &version) {
-            eprintln!("Warning: Failed to set default version: {}", e);
-        } else {
-            println!("Successfully set {} {} as default version", candidate, version);
-        }
+/// 下载并安装SDK，返回安装路径和归档校验结果
+async fn run_download_and_install(
+    candidate: &str,
+    version: &str,
+    app: &AppHandle,
+    cancel: &CancellationToken,
+) -> anyhow::Result<(PathBuf, ChecksumStatus)> {
+    // 1. 下载SDK（校验和不匹配时在此返回错误，不会进入安装步骤）
+    let downloader = Downloader::new()?;
+    let archive = downloader.download_sdk(candidate, version, app.clone(), cancel).await?;
+    println!("Downloaded {} {} archive: {} bytes", candidate, version, archive.size);
+    let temp_path = archive.path;
+
+    // 2. 安装SDK
+    let installed = if cancel.is_cancelled() {
+        Err(Cancelled.into())
     } else {
-        println!("Multiple versions ({}) installed, not setting default automatically", installed_versions.len());
+        install_blocking(temp_path.clone(), candidate, version, app, cancel).await
+    };
+
+    // 3. 清理已下载的归档（成功、失败或取消）：完整的归档不会被复用，重试时重新下载
+    if let Err(e) = std::fs::remove_file(&temp_path) {
+        eprintln!("Warning: Failed to remove temporary file {:?}: {}", temp_path, e);
     }
 
-    Ok(install_path.to_string_lossy().to_string())
+    installed.map(|path| (path, archive.checksum))
+}
+
+/// 在阻塞线程池中解压并安装归档，取消令牌随之传入
+async fn install_blocking(
+    archive_path: PathBuf,
+    candidate: &str,
+    version: &str,
+    app: &AppHandle,
+    cancel: &CancellationToken,
+) -> anyhow::Result<PathBuf> {
+    let (candidate, version, app, cancel) = (candidate.to_string(), version.to_string(), app.clone(), cancel.clone());
+    run_blocking(move || Installer::install_from_zip(&archive_path, &candidate, &version, app, &cancel)).await
 }
 
 /// 设置默认SDK版本
@@ -355,60 +762,80 @@ pub async fn download_and_install_sdk(
 pub async fn set_default_sdk_version(
     candidate: String,
     version: String,
-) -> Result<(), String> {
-    SymlinkManager::set_default_version(&candidate, &version)
-        .map_err(|e| e.to_string())
+) -> Result<(), CommandError> {
+    run_blocking(move || SymlinkManager::set_default_version(&candidate, &version))
+        .await
+        .map_err(CommandError::from)
 }
 
 /// 取消默认SDK版本设置
 #[tauri::command]
 pub async fn unset_default_sdk_version(
     candidate: String,
-) -> Result<(), String> {
-    SymlinkManager::unset_default_version(&candidate)
-        .map_err(|e| e.to_string())
+) -> Result<(), CommandError> {
+    run_blocking(move || SymlinkManager::unset_default_version(&candidate))
+        .await
+        .map_err(CommandError::from)
 }
 
 /// 加载配置
 #[tauri::command]
-pub async fn load_config() -> Result<AppConfig, String> {
+pub async fn load_config() -> Result<AppConfig, CommandError> {
     AppConfig::load()
-        .map_err(|e| e.to_string())
+        .map_err(CommandError::from)
 }
 
 /// 保存配置
+///
+/// 保存后按新配置重建 API 客户端，代理设置立即生效
 #[tauri::command]
-pub async fn save_config(config: AppConfig) -> Result<(), String> {
-    config.save()
-        .map_err(|e| e.to_string())
+pub async fn save_config(
+    config: AppConfig,
+    app: AppHandle,
+    client: State<'_, Arc<Mutex<SdkmanApiClient>>>
+) -> Result<(), CommandError> {
+    let previous = AppConfig::load().unwrap_or_default();
+    config.save()?;
+
+    if previous.offline_mode != config.offline_mode {
+        emit_network_status(&app);
+    }
+    app.state::<JobManager>().set_limit(config.max_concurrent_jobs);
+    if previous.sdkman_path != config.sdkman_path {
+        if let Err(e) = app.state::<InstalledWatcher>().restart(&app) {
+            eprintln!("Warning: Failed to watch installed SDKs: {}", e);
+        }
+    }
+
+    let new_client = SdkmanApiClient::from_config(&config)?;
+    *client.lock().await = new_client;
+
+    Ok(())
 }
 
-/// 测试代理配置
+/// 测试代理连通性
+///
+/// 按当前配置（含代理和绕过列表）实际请求 SDKMAN API，返回各阶段耗时和失败原因
 #[tauri::command]
-pub async fn test_proxy() -> Result<String, String> {
-    let config = AppConfig::load()
-        .map_err(|e| e.to_string())?;
-
-    let mut result = String::new();
-    result.push_str(&format!("Proxy Type: {}\n", config.proxy_type));
-    result.push_str(&format!("Proxy Host: {:?}\n", config.proxy_host));
-    result.push_str(&format!("Proxy Port: {:?}\n", config.proxy_port));
-
-    if let Some(proxy_url) = config.get_proxy_url() {
-        result.push_str(&format!("Proxy URL: {}\n", proxy_url));
-        result.push_str("Status: Proxy will be used for downloads\n");
-    } else {
-        result.push_str("Status: No proxy configured (direct connection)\n");
-    }
+pub async fn test_proxy() -> Result<ProbeResult, CommandError> {
+    let config = AppConfig::load()?;
+
+    println!("=== Proxy Connectivity Test ===");
+    http::log_proxy_config(&config);
+
+    let endpoint = ApiEndpoints::api_list(&config).remove(0);
+    let result = probe::probe_with_config(&config, &endpoint.candidates_list())
+        .await?;
+
+    println!("Probe result: success={}, status={:?}, failure={:?}, total={}ms",
+        result.success, result.status, result.failure, result.timings.total_ms);
 
     Ok(result)
 }
 
-/// 获取SDKMAN目录路径
+/// 获取SDKMAN目录路径（按配置、`SDKMAN_DIR`、默认目录的顺序解析）
 #[tauri::command]
-pub async fn get_sdkman_path() -> Result<String, String> {
-    let home = dirs::home_dir()
-        .ok_or("Failed to get home directory")?;
-    let sdkman_path = home.join(".sdkman");
+pub async fn get_sdkman_path() -> Result<String, CommandError> {
+    let sdkman_path = paths::sdkman_dir()?;
     Ok(sdkman_path.to_string_lossy().to_string())
 }
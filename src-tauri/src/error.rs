@@ -0,0 +1,344 @@
+use std::collections::BTreeMap;
+use std::fmt;
+use std::io;
+use serde::{Deserialize, Serialize};
+use crate::api::endpoints::EndpointUnavailable;
+use crate::jobs::{Cancelled, JobInProgress};
+use crate::network::Offline;
+
+/// 业务错误（由 `Installer`、`Downloader`、`SymlinkManager`、`SdkmanApiClient` 等放入错误链，
+/// 命令层据此生成 `CommandError`）
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SdkError {
+    /// 指定版本没有安装
+    NotInstalled { candidate: String, version: String },
+    /// 服务器返回错误状态
+    HttpStatus { status: u16 },
+    /// 下载未完成（连接提前关闭）
+    IncompleteDownload { received: u64, expected: u64 },
+    /// 归档校验和不匹配
+    ChecksumMismatch { algorithm: String, expected: String, actual: String },
+    /// 服务器未提供校验和，且设置要求校验
+    ChecksumMissing { candidate: String, version: String },
+    /// 归档格式不支持、已损坏或包含不安全的条目
+    InvalidArchive { reason: String },
+    /// 服务器响应格式无法解析
+    MalformedResponse { what: String, reason: String },
+    /// SDKMAN 目录被其他进程锁定（另一个安装、卸载或切换版本的操作正在进行）
+    Locked { holder: Option<String> },
+    /// 磁盘空间不足（`path` 为检查的目录）
+    InsufficientSpace { path: String, required: u64, available: u64 },
+    /// 等待锁期间其他进程修改了目标版本或 `current` 链接（`target` 为版本号或 `current`）
+    ConcurrentChange { candidate: String, target: String },
+}
+
+impl fmt::Display for SdkError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SdkError::NotInstalled { candidate, version } => {
+                write!(f, "Version {} of {} is not installed", version, candidate)
+            }
+            SdkError::HttpStatus { status } => write!(f, "Server responded with HTTP {}", status),
+            SdkError::IncompleteDownload { received, expected } => {
+                write!(f, "Download incomplete: received {} of {} bytes", received, expected)
+            }
+            SdkError::ChecksumMismatch { algorithm, expected, actual } => write!(
+                f,
+                "Checksum mismatch: expected {} {} but downloaded archive has {}. \
+                The download may be corrupted or tampered with.",
+                algorithm, expected, actual
+            ),
+            SdkError::ChecksumMissing { candidate, version } => write!(
+                f,
+                "Refusing to install {} {}: the server provided no checksum and unverified installs are disabled in settings",
+                candidate, version
+            ),
+            SdkError::InvalidArchive { reason } => write!(f, "Invalid archive: {}", reason),
+            SdkError::MalformedResponse { what, reason } => {
+                write!(f, "Malformed {} response: {}", what, reason)
+            }
+            SdkError::Locked { holder: Some(holder) } => {
+                write!(f, "Another SDKMAN operation is in progress: {}", holder)
+            }
+            SdkError::Locked { holder: None } => {
+                write!(f, "Another SDKMAN operation is in progress")
+            }
+            SdkError::InsufficientSpace { path, required, available } => write!(
+                f,
+                "Not enough disk space on {}: {} bytes required, {} bytes available",
+                path, required, available
+            ),
+            SdkError::ConcurrentChange { candidate, target } => write!(
+                f,
+                "{} {} was changed by another process, refresh and try again",
+                candidate, target
+            ),
+        }
+    }
+}
+
+impl std::error::Error for SdkError {}
+
+impl SdkError {
+    pub fn not_installed(candidate: &str, version: &str) -> Self {
+        SdkError::NotInstalled {
+            candidate: candidate.to_string(),
+            version: version.to_string(),
+        }
+    }
+
+    pub fn invalid_archive(reason: impl Into<String>) -> Self {
+        SdkError::InvalidArchive { reason: reason.into() }
+    }
+
+    pub fn malformed_response(what: &str, reason: impl Into<String>) -> Self {
+        SdkError::MalformedResponse {
+            what: what.to_string(),
+            reason: reason.into(),
+        }
+    }
+}
+
+/// 错误代码（稳定值，前端按代码本地化）
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ErrorCode {
+    NotInstalled,
+    NotFound,
+    Network,
+    Offline,
+    HttpStatus,
+    IncompleteDownload,
+    ChecksumMismatch,
+    ChecksumMissing,
+    InvalidArchive,
+    MalformedResponse,
+    OperationInProgress,
+    InsufficientSpace,
+    ConcurrentChange,
+    PermissionDenied,
+    Cancelled,
+    JobNotFound,
+    JobInProgress,
+    Io,
+    Internal,
+}
+
+/// 命令返回给前端的错误
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CommandError {
+    pub code: ErrorCode,
+    /// 最外层错误信息（英文，前端没有对应翻译时显示）
+    pub message: String,
+    /// 完整错误链，由外到内
+    pub context: Vec<String>,
+    /// 重试是否可能成功（网络错误、下载中断等）
+    pub retryable: bool,
+    /// 本地化参数，例如 `candidate`、`version`、`status`
+    pub params: BTreeMap<String, String>,
+}
+
+impl CommandError {
+    pub fn new(code: ErrorCode, message: impl Into<String>) -> Self {
+        let message = message.into();
+        Self {
+            code,
+            context: vec![message.clone()],
+            message,
+            retryable: false,
+            params: BTreeMap::new(),
+        }
+    }
+
+    pub fn with_param(mut self, key: &str, value: impl ToString) -> Self {
+        self.params.insert(key.to_string(), value.to_string());
+        self
+    }
+}
+
+impl fmt::Display for CommandError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.context.join(": "))
+    }
+}
+
+impl From<anyhow::Error> for CommandError {
+    fn from(error: anyhow::Error) -> Self {
+        let (code, retryable, params) = classify(&error);
+        Self {
+            code,
+            message: error.to_string(),
+            context: error.chain().map(|cause| cause.to_string()).collect(),
+            retryable,
+            params,
+        }
+    }
+}
+
+impl From<Offline> for CommandError {
+    fn from(error: Offline) -> Self {
+        anyhow::Error::new(error).into()
+    }
+}
+
+impl From<JobInProgress> for CommandError {
+    fn from(error: JobInProgress) -> Self {
+        anyhow::Error::new(error).into()
+    }
+}
+
+/// 按错误链中第一个可识别的错误确定代码、是否可重试和本地化参数
+fn classify(error: &anyhow::Error) -> (ErrorCode, bool, BTreeMap<String, String>) {
+    let mut params = BTreeMap::new();
+
+    for cause in error.chain() {
+        if cause.is::<Cancelled>() {
+            return (ErrorCode::Cancelled, false, params);
+        }
+        if let Some(offline) = cause.downcast_ref::<Offline>() {
+            params.insert("operation".to_string(), offline.operation.clone());
+            return (ErrorCode::Offline, false, params);
+        }
+        if let Some(busy) = cause.downcast_ref::<JobInProgress>() {
+            params.insert("job_id".to_string(), busy.job_id.clone());
+            params.insert("candidate".to_string(), busy.candidate.clone());
+            params.insert("version".to_string(), busy.version.clone());
+            return (ErrorCode::JobInProgress, false, params);
+        }
+        if let Some(unavailable) = cause.downcast_ref::<EndpointUnavailable>() {
+            params.insert("status".to_string(), unavailable.0.as_u16().to_string());
+            return (ErrorCode::Network, true, params);
+        }
+        if let Some(sdk_error) = cause.downcast_ref::<SdkError>() {
+            return classify_sdk_error(sdk_error, params);
+        }
+        if let Some(e) = cause.downcast_ref::<reqwest::Error>() {
+            if e.is_connect() || e.is_timeout() || e.is_request() || e.is_body() {
+                return (ErrorCode::Network, true, params);
+            }
+        }
+        if let Some(e) = cause.downcast_ref::<io::Error>() {
+            return match e.kind() {
+                io::ErrorKind::PermissionDenied => (ErrorCode::PermissionDenied, false, params),
+                io::ErrorKind::NotFound => (ErrorCode::NotFound, false, params),
+                _ => (ErrorCode::Io, false, params),
+            };
+        }
+    }
+
+    (ErrorCode::Internal, false, params)
+}
+
+fn classify_sdk_error(
+    error: &SdkError,
+    mut params: BTreeMap<String, String>,
+) -> (ErrorCode, bool, BTreeMap<String, String>) {
+    let mut param = |key: &str, value: String| {
+        params.insert(key.to_string(), value);
+    };
+
+    let (code, retryable) = match error {
+        SdkError::NotInstalled { candidate, version } => {
+            param("candidate", candidate.clone());
+            param("version", version.clone());
+            (ErrorCode::NotInstalled, false)
+        }
+        SdkError::HttpStatus { status } => {
+            param("status", status.to_string());
+            match status {
+                404 => (ErrorCode::NotFound, false),
+                500.. => (ErrorCode::HttpStatus, true),
+                _ => (ErrorCode::HttpStatus, false),
+            }
+        }
+        SdkError::IncompleteDownload { received, expected } => {
+            param("received", received.to_string());
+            param("expected", expected.to_string());
+            (ErrorCode::IncompleteDownload, true)
+        }
+        SdkError::ChecksumMismatch { algorithm, .. } => {
+            param("algorithm", algorithm.clone());
+            // 下载损坏时重新下载可能成功
+            (ErrorCode::ChecksumMismatch, true)
+        }
+        SdkError::ChecksumMissing { candidate, version } => {
+            param("candidate", candidate.clone());
+            param("version", version.clone());
+            (ErrorCode::ChecksumMissing, false)
+        }
+        SdkError::InvalidArchive { reason } => {
+            param("reason", reason.clone());
+            (ErrorCode::InvalidArchive, false)
+        }
+        SdkError::MalformedResponse { what, reason } => {
+            param("what", what.clone());
+            param("reason", reason.clone());
+            (ErrorCode::MalformedResponse, false)
+        }
+        SdkError::Locked { holder } => {
+            if let Some(holder) = holder {
+                param("holder", holder.clone());
+            }
+            // 其他操作结束后重试即可
+            (ErrorCode::OperationInProgress, true)
+        }
+        SdkError::InsufficientSpace { path, required, available } => {
+            param("path", path.clone());
+            param("required", format_mib(*required));
+            param("available", format_mib(*available));
+            (ErrorCode::InsufficientSpace, false)
+        }
+        SdkError::ConcurrentChange { candidate, target } => {
+            param("candidate", candidate.clone());
+            param("target", target.clone());
+            // 按最新状态刷新后重试即可
+            (ErrorCode::ConcurrentChange, true)
+        }
+    };
+
+    (code, retryable, params)
+}
+
+/// 字节数转换为 MB 文本（用于本地化参数）
+fn format_mib(bytes: u64) -> String {
+    format!("{:.1} MB", bytes as f64 / (1024.0 * 1024.0))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::Context;
+
+    #[test]
+    fn test_command_error_classification() {
+        let error: CommandError = Err::<(), _>(SdkError::not_installed("java", "21.0.1-tem"))
+            .context("Failed to set default version")
+            .unwrap_err()
+            .into();
+        assert_eq!(error.code, ErrorCode::NotInstalled);
+        assert!(!error.retryable);
+        assert_eq!(error.params["version"], "21.0.1-tem");
+        assert_eq!(error.context, vec![
+            "Failed to set default version".to_string(),
+            "Version 21.0.1-tem of java is not installed".to_string(),
+        ]);
+
+        let error: CommandError = anyhow::Error::new(SdkError::HttpStatus { status: 503 }).into();
+        assert_eq!((error.code, error.retryable), (ErrorCode::HttpStatus, true));
+
+        let error: CommandError = anyhow::Error::new(io::Error::from(io::ErrorKind::PermissionDenied))
+            .context("Failed to remove directory")
+            .into();
+        assert_eq!(error.code, ErrorCode::PermissionDenied);
+
+        let error: CommandError = anyhow::Error::new(Cancelled).into();
+        assert_eq!(error.code, ErrorCode::Cancelled);
+
+        let error: CommandError = anyhow::anyhow!("something unexpected").into();
+        assert_eq!(error.code, ErrorCode::Internal);
+
+        let json = serde_json::to_value(CommandError::from(Offline::new("Downloading java 21"))).unwrap();
+        assert_eq!(json["code"], "offline");
+        assert_eq!(json["params"]["operation"], "Downloading java 21");
+    }
+}
@@ -0,0 +1,353 @@
+use std::fmt;
+use std::sync::{Arc, Mutex};
+use std::sync::atomic::{AtomicU64, Ordering};
+use serde::{Deserialize, Serialize};
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+use tokio_util::sync::CancellationToken;
+use crate::local::checksum::ChecksumStatus;
+
+/// 默认同时执行的下载/安装任务数
+pub const DEFAULT_MAX_CONCURRENT_JOBS: usize = 2;
+
+/// 任务类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum JobKind {
+    Download,
+    Install,
+    DownloadAndInstall,
+}
+
+/// 任务开始事件
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct JobStarted {
+    pub job_id: String,
+    pub kind: JobKind,
+    pub candidate: String,
+    pub version: String,
+}
+
+/// 任务取消事件
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct JobCancelled {
+    pub job_id: String,
+    pub candidate: String,
+    pub version: String,
+}
+
+/// 下载/安装命令的返回值
+#[derive(Clone, Serialize, Deserialize, Debug)]
+pub struct JobResult {
+    pub job_id: String,
+    pub path: String,
+    /// 下载归档的校验结果（仅包含下载步骤的任务）
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub checksum: Option<ChecksumStatus>,
+}
+
+/// 任务被用户取消时返回的错误
+#[derive(Debug, Clone, Copy)]
+pub struct Cancelled;
+
+impl fmt::Display for Cancelled {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Operation cancelled by user")
+    }
+}
+
+impl std::error::Error for Cancelled {}
+
+/// 判断错误是否由取消操作引起
+pub fn is_cancelled(error: &anyhow::Error) -> bool {
+    error.downcast_ref::<Cancelled>().is_some()
+}
+
+/// 同一版本已有任务在排队或执行时返回的错误
+#[derive(Debug, Clone)]
+pub struct JobInProgress {
+    pub job_id: String,
+    pub candidate: String,
+    pub version: String,
+}
+
+impl fmt::Display for JobInProgress {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {} is already being processed by job {}", self.candidate, self.version, self.job_id)
+    }
+}
+
+impl std::error::Error for JobInProgress {}
+
+/// 任务状态
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum JobState {
+    /// 等待空闲槽位
+    Queued,
+    /// 正在执行
+    Running,
+}
+
+/// 任务信息（`list_jobs` 返回值和 `job-queue` 事件）
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq, Eq)]
+pub struct JobInfo {
+    pub job_id: String,
+    pub kind: JobKind,
+    pub candidate: String,
+    pub version: String,
+    pub state: JobState,
+    /// 排队位置（从 1 开始，执行中为空）
+    pub position: Option<usize>,
+}
+
+struct Job {
+    id: String,
+    kind: JobKind,
+    candidate: String,
+    version: String,
+    state: JobState,
+    token: CancellationToken,
+}
+
+/// 执行槽位的容量（由同一把锁保护，调整上限时不会与任务获取槽位交错）
+struct Capacity {
+    limit: usize,
+    /// 降低上限时从信号量中取出并持有的许可，提高上限时优先归还
+    reserved: Vec<OwnedSemaphorePermit>,
+    /// 降低上限时尚被执行中的任务占用、还需收回的许可数
+    owed: usize,
+}
+
+/// 下载/安装任务管理器（保存在 Tauri 状态中）
+///
+/// 同时执行的任务数受 `max_concurrent_jobs` 限制，其余任务按提交顺序排队；
+/// 同一候选者版本同时只允许一个任务
+pub struct JobManager {
+    /// 按提交顺序保存，用于计算排队位置
+    jobs: Mutex<Vec<Job>>,
+    next_id: AtomicU64,
+    /// 执行槽位（tokio 信号量按等待顺序分配）
+    slots: Arc<Semaphore>,
+    capacity: Mutex<Capacity>,
+}
+
+impl Default for JobManager {
+    fn default() -> Self {
+        Self::new(DEFAULT_MAX_CONCURRENT_JOBS)
+    }
+}
+
+impl JobManager {
+    pub fn new(limit: usize) -> Self {
+        let limit = limit.max(1);
+        Self {
+            jobs: Mutex::new(Vec::new()),
+            next_id: AtomicU64::new(0),
+            slots: Arc::new(Semaphore::new(limit)),
+            capacity: Mutex::new(Capacity {
+                limit,
+                reserved: Vec::new(),
+                owed: 0,
+            }),
+        }
+    }
+
+    /// 注册新任务，返回任务ID和取消令牌；同一版本已有任务时返回错误
+    pub fn start(&self, kind: JobKind, candidate: &str, version: &str) -> Result<(String, CancellationToken), JobInProgress> {
+        let mut jobs = self.jobs.lock().unwrap();
+        Self::check_idle(&jobs, candidate, version)?;
+
+        let seq = self.next_id.fetch_add(1, Ordering::Relaxed) + 1;
+        let kind_name = match kind {
+            JobKind::Download => "download",
+            JobKind::Install => "install",
+            JobKind::DownloadAndInstall => "download-install",
+        };
+        let job_id = format!("{}-{}-{}-{}", kind_name, candidate, version, seq);
+        let token = CancellationToken::new();
+
+        jobs.push(Job {
+            id: job_id.clone(),
+            kind,
+            candidate: candidate.to_string(),
+            version: version.to_string(),
+            state: JobState::Queued,
+            token: token.clone(),
+        });
+
+        Ok((job_id, token))
+    }
+
+    /// 确认指定版本没有排队或执行中的任务（如卸载前），否则返回与重复提交相同的错误
+    pub fn ensure_idle(&self, candidate: &str, version: &str) -> Result<(), JobInProgress> {
+        Self::check_idle(&self.jobs.lock().unwrap(), candidate, version)
+    }
+
+    fn check_idle(jobs: &[Job], candidate: &str, version: &str) -> Result<(), JobInProgress> {
+        match jobs.iter().find(|job| job.candidate == candidate && job.version == version) {
+            Some(existing) => Err(JobInProgress {
+                job_id: existing.id.clone(),
+                candidate: candidate.to_string(),
+                version: version.to_string(),
+            }),
+            None => Ok(()),
+        }
+    }
+
+    /// 等待空闲槽位，返回的许可在任务结束前不能释放；排队期间取消时返回 `Cancelled`
+    pub async fn wait_for_slot(&self, job_id: &str, token: &CancellationToken) -> anyhow::Result<OwnedSemaphorePermit> {
+        let permit = loop {
+            let permit = tokio::select! {
+                permit = self.slots.clone().acquire_owned() => permit?,
+                _ = token.cancelled() => return Err(Cancelled.into()),
+            };
+
+            // 上限降低后还有未收回的许可时，先收回再继续等待
+            let mut capacity = self.capacity.lock().unwrap();
+            if capacity.owed > 0 {
+                capacity.owed -= 1;
+                capacity.reserved.push(permit);
+                continue;
+            }
+            break permit;
+        };
+
+        if let Some(job) = self.jobs.lock().unwrap().iter_mut().find(|job| job.id == job_id) {
+            job.state = JobState::Running;
+        }
+
+        Ok(permit)
+    }
+
+    /// 修改同时执行的任务数（减少时等正在执行的任务结束后生效）
+    pub fn set_limit(&self, limit: usize) {
+        let limit = limit.max(1);
+        let mut capacity = self.capacity.lock().unwrap();
+
+        if limit > capacity.limit {
+            // 依次抵消未收回的许可、归还持有的许可，不足部分再新增
+            let mut added = limit - capacity.limit;
+            let cancelled = added.min(capacity.owed);
+            capacity.owed -= cancelled;
+            added -= cancelled;
+            while added > 0 && capacity.reserved.pop().is_some() {
+                added -= 1;
+            }
+            self.slots.add_permits(added);
+        } else if limit < capacity.limit {
+            // 空闲的许可立即取出，被占用的等任务结束后在 wait_for_slot 中收回
+            let mut removed = capacity.limit - limit;
+            while removed > 0 {
+                match self.slots.clone().try_acquire_owned() {
+                    Ok(permit) => capacity.reserved.push(permit),
+                    Err(_) => break,
+                }
+                removed -= 1;
+            }
+            capacity.owed += removed;
+        }
+
+        capacity.limit = limit;
+    }
+
+    /// 当前所有任务（按提交顺序）
+    pub fn list(&self) -> Vec<JobInfo> {
+        let mut position = 0;
+        self.jobs.lock().unwrap()
+            .iter()
+            .map(|job| JobInfo {
+                job_id: job.id.clone(),
+                kind: job.kind,
+                candidate: job.candidate.clone(),
+                version: job.version.clone(),
+                state: job.state,
+                position: (job.state == JobState::Queued).then(|| {
+                    position += 1;
+                    position
+                }),
+            })
+            .collect()
+    }
+
+    /// 请求取消任务
+    ///
+    /// # Returns
+    /// 任务存在时返回 true
+    pub fn cancel(&self, job_id: &str) -> bool {
+        match self.jobs.lock().unwrap().iter().find(|job| job.id == job_id) {
+            Some(job) => {
+                job.token.cancel();
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// 任务结束（成功、失败或取消）后移除
+    pub fn finish(&self, job_id: &str) {
+        self.jobs.lock().unwrap().retain(|job| job.id != job_id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[tokio::test]
+    async fn test_queue_limits_concurrency_and_rejects_duplicates() {
+        let jobs = JobManager::new(1);
+
+        let (first, first_token) = jobs.start(JobKind::DownloadAndInstall, "java", "21.0.2-tem").unwrap();
+        let (second, second_token) = jobs.start(JobKind::DownloadAndInstall, "maven", "3.9.6").unwrap();
+        let duplicate = jobs.start(JobKind::Install, "java", "21.0.2-tem").unwrap_err();
+        assert_eq!(duplicate.job_id, first);
+        assert_eq!(jobs.ensure_idle("java", "21.0.2-tem").unwrap_err().job_id, first);
+        jobs.ensure_idle("java", "17.0.9-tem").unwrap();
+
+        let first_slot = jobs.wait_for_slot(&first, &first_token).await.unwrap();
+        let positions: Vec<_> = jobs.list().iter().map(|job| (job.state, job.position)).collect();
+        assert_eq!(positions, vec![(JobState::Running, None), (JobState::Queued, Some(1))]);
+
+        // 唯一的槽位被占用，排队中的任务可以取消
+        second_token.cancel();
+        let error = jobs.wait_for_slot(&second, &second_token).await.unwrap_err();
+        assert!(is_cancelled(&error));
+        jobs.finish(&second);
+
+        drop(first_slot);
+        jobs.finish(&first);
+        assert!(jobs.list().is_empty());
+
+        // 结束后同一版本可以再次提交
+        let (again, token) = jobs.start(JobKind::Download, "java", "21.0.2-tem").unwrap();
+        let _slot = jobs.wait_for_slot(&again, &token).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_set_limit_decrease_then_increase() {
+        let jobs = JobManager::new(2);
+        let (first, token) = jobs.start(JobKind::Download, "java", "21.0.2-tem").unwrap();
+        let first_slot = jobs.wait_for_slot(&first, &token).await.unwrap();
+        let (second, token) = jobs.start(JobKind::Download, "java", "17.0.9-tem").unwrap();
+        let _second_slot = jobs.wait_for_slot(&second, &token).await.unwrap();
+
+        // 两个槽位都被占用：降低上限后，第一个任务结束释放的槽位被收回
+        jobs.set_limit(1);
+        drop(first_slot);
+        jobs.finish(&first);
+        let (third, token) = jobs.start(JobKind::Download, "maven", "3.9.6").unwrap();
+        let waiting = tokio::time::timeout(Duration::from_millis(50), jobs.wait_for_slot(&third, &token)).await;
+        assert!(waiting.is_err());
+
+        // 随后提高上限，收回的槽位立即可用，总数不会超过新的上限
+        jobs.set_limit(2);
+        let _third_slot = jobs.wait_for_slot(&third, &token).await.unwrap();
+        assert_eq!(jobs.slots.available_permits(), 0);
+
+        jobs.set_limit(1);
+        jobs.set_limit(3);
+        assert_eq!(jobs.slots.available_permits(), 1);
+        assert_eq!(jobs.capacity.lock().unwrap().owed, 0);
+    }
+}
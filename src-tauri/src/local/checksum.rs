@@ -0,0 +1,316 @@
+use std::collections::HashMap;
+use std::fs;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use anyhow::{Result, Context};
+use reqwest::header::HeaderMap;
+use serde::{Deserialize, Serialize};
+use sha2::digest::DynDigest;
+use crate::error::SdkError;
+
+const CHECKSUM_HEADER_PREFIX: &str = "x-sdkman-checksum-";
+const CHECKSUM_RECORDS_FILE: &str = "checksums.json";
+
+/// 并行的安装/卸载任务可能同时修改校验记录，读取-修改-写入期间持有此锁
+static RECORDS_LOCK: Mutex<()> = Mutex::new(());
+
+/// 校验和算法（SDKMAN broker 通过 `X-Sdkman-Checksum-<算法>` 响应头提供）
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ChecksumAlgorithm {
+    Md5,
+    Sha1,
+    Sha256,
+    Sha384,
+    Sha512,
+}
+
+impl ChecksumAlgorithm {
+    /// 从响应头中的算法名称解析（如 `SHA-256`、`MD5`）
+    pub fn from_header_name(name: &str) -> Option<Self> {
+        match name.to_ascii_uppercase().replace('-', "").as_str() {
+            "MD5" => Some(Self::Md5),
+            "SHA1" => Some(Self::Sha1),
+            "SHA256" => Some(Self::Sha256),
+            "SHA384" => Some(Self::Sha384),
+            "SHA512" => Some(Self::Sha512),
+            _ => None,
+        }
+    }
+
+    /// 算法显示名称
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Md5 => "MD5",
+            Self::Sha1 => "SHA-1",
+            Self::Sha256 => "SHA-256",
+            Self::Sha384 => "SHA-384",
+            Self::Sha512 => "SHA-512",
+        }
+    }
+
+    fn hasher(&self) -> Box<dyn DynDigest + Send> {
+        match self {
+            Self::Md5 => Box::new(md5::Md5::default()),
+            Self::Sha1 => Box::new(sha1::Sha1::default()),
+            Self::Sha256 => Box::new(sha2::Sha256::default()),
+            Self::Sha384 => Box::new(sha2::Sha384::default()),
+            Self::Sha512 => Box::new(sha2::Sha512::default()),
+        }
+    }
+}
+
+/// 服务器提供的期望校验和
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExpectedChecksum {
+    pub algorithm: ChecksumAlgorithm,
+    pub value: String,
+}
+
+impl ExpectedChecksum {
+    /// 从响应头中提取所有校验和，按算法强度从高到低排序
+    pub fn from_headers(headers: &HeaderMap) -> Vec<ExpectedChecksum> {
+        let mut checksums: Vec<ExpectedChecksum> = headers.iter()
+            .filter_map(|(name, value)| {
+                let algorithm = name.as_str().strip_prefix(CHECKSUM_HEADER_PREFIX)?;
+                let algorithm = ChecksumAlgorithm::from_header_name(algorithm)?;
+                let value = value.to_str().ok()?.trim().to_ascii_lowercase();
+                if value.is_empty() {
+                    return None;
+                }
+                Some(ExpectedChecksum { algorithm, value })
+            })
+            .collect();
+
+        checksums.sort_by_key(|c| std::cmp::Reverse(c.algorithm));
+        checksums
+    }
+}
+
+/// 校验结果（会记录下来并展示在界面上）
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum ChecksumStatus {
+    /// 校验和匹配
+    Verified { algorithm: String },
+    /// 服务器没有提供校验和
+    Unverified,
+}
+
+/// 边下载边计算校验和
+pub struct ArchiveHasher {
+    expected: Option<ExpectedChecksum>,
+    hasher: Option<Box<dyn DynDigest + Send>>,
+}
+
+impl ArchiveHasher {
+    /// 使用最强的可用算法创建；没有校验和时不计算
+    pub fn new(checksums: &[ExpectedChecksum]) -> Self {
+        let expected = checksums.first().cloned();
+        let hasher = expected.as_ref().map(|c| c.algorithm.hasher());
+        Self { expected, hasher }
+    }
+
+    pub fn update(&mut self, data: &[u8]) {
+        if let Some(hasher) = self.hasher.as_mut() {
+            hasher.update(data);
+        }
+    }
+
+    /// 续传时先把已下载的部分计入校验和
+    pub fn update_from_file(&mut self, path: &Path) -> Result<()> {
+        if self.hasher.is_none() {
+            return Ok(());
+        }
+
+        let mut file = fs::File::open(path)
+            .context("Failed to open partial download for hashing")?;
+        let mut buffer = vec![0u8; 64 * 1024];
+        loop {
+            let read = file.read(&mut buffer)
+                .context("Failed to read partial download for hashing")?;
+            if read == 0 {
+                break;
+            }
+            self.update(&buffer[..read]);
+        }
+
+        Ok(())
+    }
+
+    /// 完成计算并与期望值比较
+    pub fn finish(self) -> Result<ChecksumStatus> {
+        let (Some(expected), Some(hasher)) = (self.expected, self.hasher) else {
+            return Ok(ChecksumStatus::Unverified);
+        };
+
+        let actual = hex::encode(hasher.finalize());
+        if actual != expected.value {
+            return Err(SdkError::ChecksumMismatch {
+                algorithm: expected.algorithm.name().to_string(),
+                expected: expected.value,
+                actual,
+            }.into());
+        }
+
+        Ok(ChecksumStatus::Verified {
+            algorithm: expected.algorithm.name().to_string(),
+        })
+    }
+}
+
+/// 获取校验记录文件路径（与配置文件相同的目录）
+fn records_path() -> Result<PathBuf> {
+    let home = dirs::home_dir()
+        .context("Failed to get home directory")?;
+
+    let config_dir = home.join(".config").join("sdkman-gui");
+
+    if !config_dir.exists() {
+        fs::create_dir_all(&config_dir)
+            .context("Failed to create config directory")?;
+    }
+
+    Ok(config_dir.join(CHECKSUM_RECORDS_FILE))
+}
+
+fn record_key(candidate: &str, version: &str) -> String {
+    format!("{}/{}", candidate, version)
+}
+
+/// 读取所有校验记录
+pub fn read_records() -> Result<HashMap<String, ChecksumStatus>> {
+    read_records_from(&records_path()?)
+}
+
+fn read_records_from(path: &Path) -> Result<HashMap<String, ChecksumStatus>> {
+    if !path.exists() {
+        return Ok(HashMap::new());
+    }
+
+    let content = fs::read_to_string(path)
+        .context("Failed to read checksum records")?;
+
+    serde_json::from_str(&content)
+        .context("Failed to parse checksum records")
+}
+
+/// 在锁内读取、修改并写回校验记录；`update` 返回 false 时不写回
+///
+/// 先写入临时文件再重命名，写入中途退出不会留下不完整的记录文件
+fn update_records_in(
+    path: &Path,
+    update: impl FnOnce(&mut HashMap<String, ChecksumStatus>) -> bool,
+) -> Result<()> {
+    let _guard = RECORDS_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+
+    let mut records = read_records_from(path).unwrap_or_default();
+    if !update(&mut records) {
+        return Ok(());
+    }
+
+    let json = serde_json::to_string_pretty(&records)
+        .context("Failed to serialize checksum records")?;
+
+    let temp_path = path.with_extension("json.tmp");
+    fs::write(&temp_path, json)
+        .context("Failed to write checksum records")?;
+    fs::rename(&temp_path, path)
+        .context("Failed to write checksum records")?;
+
+    Ok(())
+}
+
+/// 记录某个已安装版本的校验结果
+pub fn record_status(candidate: &str, version: &str, status: &ChecksumStatus) -> Result<()> {
+    update_records_in(&records_path()?, |records| {
+        records.insert(record_key(candidate, version), status.clone());
+        true
+    })
+}
+
+/// 获取某个已安装版本的校验结果
+pub fn get_status(
+    records: &HashMap<String, ChecksumStatus>,
+    candidate: &str,
+    version: &str,
+) -> Option<ChecksumStatus> {
+    records.get(&record_key(candidate, version)).cloned()
+}
+
+/// 卸载后删除校验记录
+pub fn remove_status(candidate: &str, version: &str) -> Result<()> {
+    update_records_in(&records_path()?, |records| {
+        records.remove(&record_key(candidate, version)).is_some()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reqwest::header::HeaderValue;
+
+    #[test]
+    fn test_checksums_from_headers_prefers_strongest() {
+        let mut headers = HeaderMap::new();
+        headers.insert("X-Sdkman-Checksum-MD5", HeaderValue::from_static("ABC"));
+        headers.insert("X-Sdkman-Checksum-SHA-256", HeaderValue::from_static("DEF"));
+        headers.insert("X-Sdkman-Checksum-Unknown", HeaderValue::from_static("123"));
+        headers.insert("X-Sdkman-ArchiveType", HeaderValue::from_static("zip"));
+
+        let checksums = ExpectedChecksum::from_headers(&headers);
+        assert_eq!(checksums.len(), 2);
+        assert_eq!(checksums[0].algorithm, ChecksumAlgorithm::Sha256);
+        assert_eq!(checksums[0].value, "def");
+        assert_eq!(checksums[1].algorithm, ChecksumAlgorithm::Md5);
+    }
+
+    #[test]
+    fn test_hasher_verifies_sha256() {
+        let expected = ExpectedChecksum {
+            algorithm: ChecksumAlgorithm::Sha256,
+            // sha256("hello world")
+            value: "b94d27b9934d3e08a52e52d7da7dabfac484efe37a5380ee9088f7ace2efcde9".to_string(),
+        };
+
+        let mut hasher = ArchiveHasher::new(std::slice::from_ref(&expected));
+        hasher.update(b"hello ");
+        hasher.update(b"world");
+        assert_eq!(
+            hasher.finish().unwrap(),
+            ChecksumStatus::Verified { algorithm: "SHA-256".to_string() }
+        );
+
+        let mut hasher = ArchiveHasher::new(&[expected]);
+        hasher.update(b"tampered");
+        assert!(hasher.finish().is_err());
+    }
+
+    #[test]
+    fn test_hasher_without_checksum_is_unverified() {
+        let mut hasher = ArchiveHasher::new(&[]);
+        hasher.update(b"anything");
+        assert_eq!(hasher.finish().unwrap(), ChecksumStatus::Unverified);
+    }
+
+    #[test]
+    fn test_concurrent_updates_keep_all_records() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(CHECKSUM_RECORDS_FILE);
+
+        std::thread::scope(|scope| {
+            for i in 0..8 {
+                let path = &path;
+                scope.spawn(move || {
+                    update_records_in(path, |records| {
+                        records.insert(record_key("java", &i.to_string()), ChecksumStatus::Unverified);
+                        true
+                    }).unwrap();
+                });
+            }
+        });
+
+        assert_eq!(read_records_from(&path).unwrap().len(), 8);
+        assert!(!path.with_extension("json.tmp").exists());
+    }
+}
@@ -0,0 +1,139 @@
+use std::fs::{self, File};
+use std::io::{Read, Seek, SeekFrom};
+use std::path::Path;
+use anyhow::{Result, Context};
+use zip::ZipArchive;
+use crate::error::SdkError;
+
+/// 预留空间，避免把磁盘写满
+const SAFETY_MARGIN: u64 = 64 * 1024 * 1024;
+/// 每个文件按半个块估算的额外占用（小文件向上取整到块大小）
+const PER_ENTRY_OVERHEAD: u64 = 2048;
+/// gzip 尾部大小不可信时使用的压缩比
+const TAR_GZ_RATIO: u64 = 3;
+
+/// 估算归档解压后的大小（字节）
+///
+/// - ZIP：累加中央目录中记录的各条目大小
+/// - tar.gz：读取 gzip 尾部的 ISIZE（原始大小对 2^32 取模）；
+///   小于归档本身时说明溢出或为多段 gzip，改用压缩比估算
+pub fn estimate_uncompressed_size(archive_path: &Path) -> Result<u64> {
+    let file_name = archive_path.file_name()
+        .and_then(|n| n.to_str())
+        .unwrap_or("");
+
+    if file_name.ends_with(".zip") {
+        estimate_zip(archive_path)
+    } else {
+        estimate_tar_gz(archive_path)
+    }
+}
+
+fn estimate_zip(zip_path: &Path) -> Result<u64> {
+    let file = File::open(zip_path)
+        .context(format!("Failed to open ZIP file: {:?}", zip_path))?;
+    let mut archive = ZipArchive::new(file)
+        .map_err(|e| SdkError::invalid_archive(format!("Failed to read ZIP archive: {}", e)))?;
+
+    let mut total = 0u64;
+    for i in 0..archive.len() {
+        let entry = archive.by_index_raw(i)
+            .map_err(|e| SdkError::invalid_archive(format!("Failed to read ZIP entry {}: {}", i, e)))?;
+        total = total.saturating_add(entry.size()).saturating_add(PER_ENTRY_OVERHEAD);
+    }
+
+    Ok(total)
+}
+
+fn estimate_tar_gz(tar_gz_path: &Path) -> Result<u64> {
+    let archive_size = fs::metadata(tar_gz_path)
+        .context(format!("Failed to get archive metadata: {:?}", tar_gz_path))?
+        .len();
+
+    let isize = read_gzip_isize(tar_gz_path).unwrap_or(0);
+    if isize >= archive_size {
+        Ok(isize)
+    } else {
+        Ok(archive_size.saturating_mul(TAR_GZ_RATIO))
+    }
+}
+
+/// 读取 gzip 尾部最后 4 字节（小端序的原始数据大小）
+fn read_gzip_isize(path: &Path) -> Result<u64> {
+    let mut file = File::open(path)?;
+    file.seek(SeekFrom::End(-4))?;
+    let mut trailer = [0u8; 4];
+    file.read_exact(&mut trailer)?;
+    Ok(u32::from_le_bytes(trailer) as u64)
+}
+
+/// 检查 `path` 所在磁盘是否还有 `required` 字节（另加预留空间）的可用空间
+///
+/// `path` 不存在时检查最近的已存在上级目录；无法获取可用空间时只打印警告
+pub fn ensure_available(path: &Path, required: u64) -> Result<()> {
+    let Some(existing) = path.ancestors().find(|p| p.exists()) else {
+        return Ok(());
+    };
+
+    let available = match fs4::available_space(existing) {
+        Ok(available) => available,
+        Err(e) => {
+            eprintln!("Warning: Failed to get free space of {:?}: {}", existing, e);
+            return Ok(());
+        }
+    };
+
+    let required = required.saturating_add(SAFETY_MARGIN);
+    println!("Disk space at {:?}: {} bytes required, {} bytes available", existing, required, available);
+
+    if available < required {
+        return Err(SdkError::InsufficientSpace {
+            path: existing.to_string_lossy().to_string(),
+            required,
+            available,
+        }.into());
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+    use flate2::write::GzEncoder;
+    use flate2::Compression;
+
+    #[test]
+    fn test_estimate_uncompressed_size() {
+        let dir = tempfile::tempdir().unwrap();
+        let content = vec![b'x'; 256 * 1024];
+
+        let zip_path = dir.path().join("sdk.zip");
+        let mut zip = zip::ZipWriter::new(File::create(&zip_path).unwrap());
+        for name in ["sdk/bin/tool", "sdk/lib/tool.jar"] {
+            zip.start_file(name, zip::write::SimpleFileOptions::default()).unwrap();
+            zip.write_all(&content).unwrap();
+        }
+        zip.finish().unwrap();
+        assert_eq!(estimate_uncompressed_size(&zip_path).unwrap(), 2 * (content.len() as u64 + PER_ENTRY_OVERHEAD));
+
+        let tar_gz_path = dir.path().join("sdk.tar.gz");
+        let mut gz = GzEncoder::new(File::create(&tar_gz_path).unwrap(), Compression::default());
+        gz.write_all(&content).unwrap();
+        gz.finish().unwrap();
+        assert_eq!(estimate_uncompressed_size(&tar_gz_path).unwrap(), content.len() as u64);
+    }
+
+    #[test]
+    fn test_ensure_available_reports_shortfall() {
+        let dir = tempfile::tempdir().unwrap();
+        ensure_available(&dir.path().join("not/yet/created"), 0).unwrap();
+
+        let err = ensure_available(dir.path(), u64::MAX / 2).unwrap_err();
+        match err.downcast_ref::<SdkError>() {
+            Some(SdkError::InsufficientSpace { required, available, .. }) => assert!(available < required),
+            other => panic!("unexpected error: {:?}", other),
+        }
+    }
+}
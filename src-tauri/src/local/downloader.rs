@@ -1,12 +1,20 @@
-use std::path::PathBuf;
-use std::fs::File;
+use std::path::{Path, PathBuf};
+use std::fs::{self, File, OpenOptions};
 use std::io::Write;
-use reqwest::Client;
+use reqwest::{Client, Response, StatusCode};
+use reqwest::header::{HeaderName, CONTENT_RANGE, ETAG, IF_RANGE, LAST_MODIFIED, LOCATION, RANGE};
 use anyhow::{Result, Context};
 use serde::{Deserialize, Serialize};
 use tauri::{Emitter, AppHandle};
-use crate::api::{endpoints::ApiEndpoints, detect_platform};
+use crate::api::detect_platform;
+use crate::api::endpoints::{with_failover, ApiEndpoints, EndpointUnavailable, EndpointUsed};
+use crate::api::http::{self, ClientKind};
 use crate::config::AppConfig;
+use crate::error::SdkError;
+use crate::jobs::Cancelled;
+use super::checksum::{ArchiveHasher, ChecksumStatus, ExpectedChecksum};
+use super::disk;
+use tokio_util::sync::CancellationToken;
 
 /// 下载进度事件
 #[derive(Clone, Serialize, Deserialize, Debug)]
@@ -16,11 +24,62 @@ pub struct DownloadProgress {
     pub downloaded: u64,
     pub total: u64,
     pub percentage: f64,
+    /// 续传起始位置（完整下载时为 0）
+    pub resumed_from: u64,
+}
+
+/// 未完成下载的元数据（用于断点续传）
+#[derive(Clone, Serialize, Deserialize, Debug)]
+struct PartialDownload {
+    /// 请求的下载地址
+    url: String,
+    etag: Option<String>,
+    last_modified: Option<String>,
+    /// 已写入暂存文件的字节数
+    downloaded: u64,
+    /// 文件总大小（未知时为 0）
+    total: u64,
+    /// 归档文件扩展名（zip 或 tar.gz）
+    file_extension: String,
+}
+
+impl PartialDownload {
+    /// If-Range 使用的校验值：优先使用强 ETag，其次是 Last-Modified
+    fn if_range_validator(&self) -> Option<&str> {
+        self.etag.as_deref()
+            .filter(|etag| !etag.starts_with("W/"))
+            .or(self.last_modified.as_deref())
+    }
+}
+
+/// 已下载的归档文件
+#[derive(Clone, Debug)]
+pub struct DownloadedArchive {
+    pub path: PathBuf,
+    /// 归档大小（字节）
+    pub size: u64,
+    pub checksum: ChecksumStatus,
+}
+
+/// broker 的响应信息（重定向之前的响应头）
+struct BrokerResolution {
+    /// 实际下载地址（重定向目标）
+    download_url: String,
+    /// X-Sdkman-ArchiveType 响应头
+    archive_type: Option<String>,
+    /// X-Sdkman-Checksum-* 响应头
+    checksums: Vec<ExpectedChecksum>,
 }
 
 /// SDK 下载器
 pub struct Downloader {
     client: Client,
+    /// 不跟随重定向的客户端，用于读取 broker 的响应头
+    broker_client: Client,
+    /// 没有校验和时是否拒绝安装
+    require_checksum: bool,
+    /// 按优先级排列的下载端点（主地址 + 镜像）
+    endpoints: Vec<ApiEndpoints>,
 }
 
 impl Downloader {
@@ -30,138 +89,295 @@ impl Downloader {
         let config = AppConfig::load().unwrap_or_default();
 
         println!("=== Downloader Configuration ===");
-        println!("Proxy type: {}", config.proxy_type);
-        println!("Proxy host: {:?}", config.proxy_host);
-        println!("Proxy port: {:?}", config.proxy_port);
-
-        let mut client_builder = Client::builder()
-            .timeout(std::time::Duration::from_secs(300))  // 5分钟总超时
-            .connect_timeout(std::time::Duration::from_secs(30))  // 30秒连接超时
-            .redirect(reqwest::redirect::Policy::limited(10));  // 允许最多10次重定向
-
-        // 如果配置了代理，应用代理设置
-        if let Some(proxy_url) = config.get_proxy_url() {
-            println!("✓ Using proxy: {}", proxy_url);
-            if let Ok(proxy) = reqwest::Proxy::all(&proxy_url) {
-                client_builder = client_builder.proxy(proxy);
-                println!("✓ Proxy configured successfully");
-            } else {
-                println!("✗ Failed to configure proxy");
-            }
+        http::log_proxy_config(&config);
+
+        let client = http::build_client(&config, ClientKind::Download)?;
+        let broker_client = http::build_client(&config, ClientKind::Broker)?;
+
+        Ok(Self {
+            client,
+            broker_client,
+            require_checksum: config.require_checksum,
+            endpoints: ApiEndpoints::download_list(&config),
+        })
+    }
+
+    /// 请求 broker 下载地址，读取重定向响应中的归档类型和校验和
+    ///
+    /// broker 通过 302 重定向到实际的下载地址，`X-Sdkman-*` 响应头只存在于
+    /// 重定向响应中，自动跟随重定向会丢失这些信息。
+    async fn resolve_broker(&self, url: &str) -> Result<BrokerResolution> {
+        let response = self.broker_client.get(url)
+            .send()
+            .await
+            .context("Failed to send download request")?;
+
+        let status = response.status();
+        let archive_type = header_string(&response, HeaderName::from_static("x-sdkman-archivetype"));
+        let checksums = ExpectedChecksum::from_headers(response.headers());
+
+        let download_url = if status.is_redirection() {
+            let location = response.headers()
+                .get(LOCATION)
+                .and_then(|v| v.to_str().ok())
+                .context("Download redirect is missing the Location header")?;
+            response.url()
+                .join(location)
+                .context("Invalid download redirect location")?
+                .to_string()
+        } else if status.is_success() {
+            // 镜像直接提供文件，没有重定向
+            url.to_string()
+        } else if status.is_server_error() {
+            return Err(EndpointUnavailable(status))
+                .context("Download broker unavailable");
         } else {
-            println!("✗ No proxy configured (direct connection)");
+            return Err(SdkError::HttpStatus { status: status.as_u16() }).context("Download failed");
+        };
+
+        Ok(BrokerResolution {
+            download_url,
+            archive_type,
+            checksums,
+        })
+    }
+
+    /// 获取下载暂存目录（存放未完成的下载及其元数据）
+    fn get_staging_dir() -> Result<PathBuf> {
+        let home = dirs::home_dir()
+            .context("Failed to get home directory")?;
+
+        let staging_dir = home.join(".config").join("sdkman-gui").join("downloads");
+
+        // 确保暂存目录存在
+        if !staging_dir.exists() {
+            fs::create_dir_all(&staging_dir)
+                .context("Failed to create download staging directory")?;
+        }
+
+        Ok(staging_dir)
+    }
+
+    /// 读取可续传的下载记录
+    ///
+    /// 只有在元数据与请求地址一致、带有强校验值、且暂存文件至少包含
+    /// 已记录字节数时才允许续传；暂存文件会被截断到已记录的长度。
+    fn load_partial(meta_path: &Path, part_path: &Path, url: &str) -> Option<PartialDownload> {
+        let content = fs::read_to_string(meta_path).ok()?;
+        let partial: PartialDownload = serde_json::from_str(&content).ok()?;
+
+        if partial.url != url || partial.downloaded == 0 || partial.if_range_validator().is_none() {
+            return None;
+        }
+
+        let part_len = fs::metadata(part_path).ok()?.len();
+        if part_len < partial.downloaded {
+            return None;
+        }
+
+        // 丢弃最后一次记录之后写入的字节，它们可能不完整
+        if part_len > partial.downloaded {
+            let file = OpenOptions::new().write(true).open(part_path).ok()?;
+            file.set_len(partial.downloaded).ok()?;
         }
 
-        let client = client_builder
-            .build()
-            .context("Failed to create HTTP client")?;
+        Some(partial)
+    }
+
+    /// 保存下载记录
+    fn save_partial(meta_path: &Path, partial: &PartialDownload) -> Result<()> {
+        let json = serde_json::to_string_pretty(partial)
+            .context("Failed to serialize download metadata")?;
 
-        Ok(Self { client })
+        fs::write(meta_path, json)
+            .context("Failed to write download metadata")?;
+
+        Ok(())
+    }
+
+    /// 删除暂存文件及其元数据
+    fn discard_partial(meta_path: &Path, part_path: &Path) {
+        let _ = fs::remove_file(meta_path);
+        let _ = fs::remove_file(part_path);
     }
 
     /// 下载SDK并返回临时文件路径
     ///
+    /// 未完成的下载保存在暂存目录中，下次下载同一版本时通过 HTTP Range 续传；
+    /// 服务器忽略 Range 或文件已变化（If-Range 校验失败）时自动回退为完整下载。
+    ///
     /// # Arguments
     /// * `candidate` - SDK 候选者名称
     /// * `version` - 版本号
     /// * `app` - Tauri 应用句柄，用于发送进度事件
+    /// * `cancel` - 取消令牌；取消时删除暂存文件并返回 [`Cancelled`] 错误
     ///
     /// # Returns
-    /// 返回下载的临时文件路径
+    /// 返回下载的临时文件路径及校验结果；校验和不匹配时返回错误
     pub async fn download_sdk(
         &self,
         candidate: &str,
         version: &str,
         app: AppHandle,
-    ) -> Result<PathBuf> {
+        cancel: &CancellationToken,
+    ) -> Result<DownloadedArchive> {
         // 1. 获取下载URL
         let platform = detect_platform();
-        let url = ApiEndpoints::download(candidate, version, &platform);
 
         println!("=== Download Information ===");
         println!("Candidate: {}", candidate);
         println!("Version: {}", version);
         println!("Platform: {}", platform);
-        println!("Initial URL: {}", url);
 
-        // 2. 发送HTTP请求
-        let response = self.client.get(&url)
-            .send()
-            .await
-            .context("Failed to send download request")?;
+        let staging_dir = Self::get_staging_dir()?;
+        let part_path = staging_dir.join(format!("{}-{}.part", candidate, version));
+        let meta_path = staging_dir.join(format!("{}-{}.json", candidate, version));
+
+        // 2. 通过 broker 获取实际下载地址、归档类型和校验和（主地址不可达时切换镜像）
+        let ((url, broker), served_by) = with_failover(&self.endpoints, |endpoint| {
+            let url = endpoint.download(candidate, version, &platform);
+            async move {
+                println!("Initial URL: {}", url);
+                let broker = self.resolve_broker(&url).await?;
+                Ok((url, broker))
+            }
+        }).await?;
+        let download_url = broker.download_url.as_str();
+
+        let _ = app.emit("api-endpoint", &EndpointUsed::new("download", Some(candidate), &served_by));
+
+        match broker.checksums.first() {
+            Some(checksum) => println!("Checksum ({}): {}", checksum.algorithm.name(), checksum.value),
+            None => println!("No checksum provided by server"),
+        }
+
+        // 要求校验时，服务器没有提供校验和就不必开始下载
+        if self.require_checksum && broker.checksums.is_empty() {
+            Self::discard_partial(&meta_path, &part_path);
+            return Err(SdkError::ChecksumMissing {
+                candidate: candidate.to_string(),
+                version: version.to_string(),
+            }.into());
+        }
+
+        // 3. 发送HTTP请求（如有未完成的下载，尝试续传）
+        let mut partial = Self::load_partial(&meta_path, &part_path, &url);
+
+        let response = match partial.as_ref() {
+            Some(previous) => {
+                println!("Resuming download from byte {}", previous.downloaded);
+
+                let mut request = self.client.get(download_url)
+                    .header(RANGE, format!("bytes={}-", previous.downloaded));
+                if let Some(validator) = previous.if_range_validator() {
+                    request = request.header(IF_RANGE, validator);
+                }
+
+                let response = request.send()
+                    .await
+                    .context("Failed to send download request")?;
+
+                let range_start = response.headers()
+                    .get(CONTENT_RANGE)
+                    .and_then(|v| v.to_str().ok())
+                    .and_then(parse_content_range)
+                    .map(|(start, _)| start);
+
+                if response.status() == StatusCode::PARTIAL_CONTENT
+                    && range_start == Some(previous.downloaded)
+                {
+                    response
+                } else if response.status() == StatusCode::OK {
+                    // 服务器忽略了 Range，或文件已变化：使用完整响应重新下载
+                    println!("Server ignored range request or file changed, restarting download");
+                    partial = None;
+                    response
+                } else {
+                    println!("Cannot resume download (status: {}), restarting", response.status());
+                    partial = None;
+                    Self::discard_partial(&meta_path, &part_path);
+                    self.client.get(download_url)
+                        .send()
+                        .await
+                        .context("Failed to send download request")?
+                }
+            }
+            None => {
+                self.client.get(download_url)
+                    .send()
+                    .await
+                    .context("Failed to send download request")?
+            }
+        };
 
         // 检查响应状态
         if !response.status().is_success() {
-            anyhow::bail!("Download failed with status: {}", response.status());
+            return Err(SdkError::HttpStatus { status: response.status().as_u16() }).context("Download failed");
         }
 
-        // 记录最终的URL（重定向后）
-        let final_url = response.url().clone();
-        println!("Final URL (after redirects): {}", final_url);
+        println!("Final URL (after redirects): {}", response.url());
+
+        // 续传时从已下载的字节数开始计算
+        let resumed_from = partial.as_ref().map(|p| p.downloaded).unwrap_or(0);
 
         // 获取文件总大小
-        let total_size = response.content_length().unwrap_or(0);
+        let total_size = match partial.as_ref() {
+            Some(previous) => response.headers()
+                .get(CONTENT_RANGE)
+                .and_then(|v| v.to_str().ok())
+                .and_then(parse_content_range)
+                .and_then(|(_, total)| total)
+                .or_else(|| response.content_length().map(|len| resumed_from + len))
+                .unwrap_or(previous.total),
+            None => response.content_length().unwrap_or(0),
+        };
 
         println!("Total size: {} bytes", total_size);
 
-        // 3. 检测文件类型并创建临时文件
-        // 优先使用 SDKMAN 的 X-Sdkman-ArchiveType 响应头
-        let archive_type = response.headers()
-            .get("x-sdkman-archivetype")
-            .and_then(|v| v.to_str().ok())
-            .unwrap_or("");
-
-        let content_type = response.headers()
-            .get("content-type")
-            .and_then(|v| v.to_str().ok())
-            .unwrap_or("");
-
-        // 检查 Content-Disposition 头中的文件名
-        let content_disposition = response.headers()
-            .get("content-disposition")
-            .and_then(|v| v.to_str().ok())
-            .unwrap_or("");
-
-        // 根据多种因素确定文件扩展名
-        let file_extension = if !archive_type.is_empty() {
-            // 优先使用 SDKMAN 提供的类型
-            if archive_type == "zip" {
-                "zip"
-            } else if archive_type == "tar.gz" || archive_type == "tgz" {
-                "tar.gz"
-            } else {
-                archive_type  // 直接使用
-            }
-        } else if content_disposition.contains(".zip") {
-            "zip"
-        } else if content_disposition.contains(".tar.gz") || content_disposition.contains(".tgz") {
-            "tar.gz"
-        } else if content_type.contains("zip") || content_type.contains("application/x-zip") {
-            "zip"
-        } else if content_type.contains("gzip") || content_type.contains("x-gzip") {
-            "tar.gz"
-        } else {
-            // 某些 SDK（如 Gradle）默认为 zip
-            // 根据 candidate 类型判断
-            match candidate {
-                "gradle" | "maven" | "ant" => "zip",
-                _ => "tar.gz",  // JDK 等默认 tar.gz
-            }
+        // 下载目录所在磁盘需要容纳剩余部分（服务器未返回大小时在下载过程中检查）
+        disk::ensure_available(&staging_dir, total_size.saturating_sub(resumed_from))
+            .context(format!("Cannot download {} {}", candidate, version))?;
+
+        // 确定文件类型（续传时沿用上次检测到的类型）
+        let file_extension = match partial.as_ref() {
+            Some(previous) => previous.file_extension.clone(),
+            None => detect_file_extension(&response, broker.archive_type.as_deref(), candidate),
         };
 
-        println!("X-Sdkman-ArchiveType: {}", archive_type);
-        println!("Content-Type: {}", content_type);
-        println!("Content-Disposition: {}", content_disposition);
-        println!("Using file extension: {}", file_extension);
+        let etag = header_string(&response, ETAG)
+            .or_else(|| partial.as_ref().and_then(|p| p.etag.clone()));
+        let last_modified = header_string(&response, LAST_MODIFIED)
+            .or_else(|| partial.as_ref().and_then(|p| p.last_modified.clone()));
+
+        let mut record = PartialDownload {
+            url: url.clone(),
+            etag,
+            last_modified,
+            downloaded: resumed_from,
+            total: total_size,
+            file_extension: file_extension.clone(),
+        };
+        Self::save_partial(&meta_path, &record)?;
 
-        let temp_dir = std::env::temp_dir();
-        let temp_file_path = temp_dir.join(format!("{}-{}.{}", candidate, version, file_extension));
-        let mut temp_file = File::create(&temp_file_path)
-            .context("Failed to create temporary file")?;
+        let mut temp_file = if resumed_from > 0 {
+            OpenOptions::new()
+                .append(true)
+                .open(&part_path)
+                .context("Failed to open partial download file")?
+        } else {
+            File::create(&part_path)
+                .context("Failed to create temporary file")?
+        };
+
+        // 边下载边计算校验和（续传时先计入已下载的部分）
+        let mut hasher = ArchiveHasher::new(&broker.checksums);
+        if resumed_from > 0 {
+            hasher.update_from_file(&part_path)?;
+        }
 
         // 4. 下载文件，报告进度
-        let mut downloaded: u64 = 0;
-        let mut last_reported_downloaded: u64 = 0;
+        let mut downloaded: u64 = resumed_from;
+        let mut last_reported_downloaded: u64 = resumed_from;
         let mut last_reported_percentage: f64 = 0.0;
         let mut stream = response.bytes_stream();
 
@@ -171,10 +387,57 @@ impl Downloader {
         const REPORT_INTERVAL_BYTES: u64 = 1024 * 1024; // 1MB
         const REPORT_INTERVAL_PERCENTAGE: f64 = 1.0; // 1%
 
-        while let Some(chunk) = stream.next().await {
-            let chunk = chunk.context("Failed to read chunk")?;
+        // 续传时立即报告起始位置
+        if resumed_from > 0 {
+            let percentage = if total_size > 0 {
+                (resumed_from as f64 / total_size as f64) * 100.0
+            } else {
+                0.0
+            };
+            let progress = DownloadProgress {
+                candidate: candidate.to_string(),
+                version: version.to_string(),
+                downloaded,
+                total: total_size,
+                percentage,
+                resumed_from,
+            };
+            let _ = app.emit("download-progress", &progress);
+            last_reported_percentage = percentage;
+        }
+
+        loop {
+            let next = tokio::select! {
+                _ = cancel.cancelled() => None,
+                next = stream.next() => Some(next),
+            };
+
+            // 用户取消：删除暂存文件，不保留续传记录
+            let Some(next) = next else {
+                drop(temp_file);
+                Self::discard_partial(&meta_path, &part_path);
+                println!("Download of {} {} cancelled", candidate, version);
+                return Err(Cancelled.into());
+            };
+
+            let Some(chunk) = next else {
+                break;
+            };
+
+            let chunk = match chunk {
+                Ok(chunk) => chunk,
+                Err(e) => {
+                    // 保留已下载的部分，以便下次续传
+                    if temp_file.flush().is_ok() {
+                        record.downloaded = downloaded;
+                        let _ = Self::save_partial(&meta_path, &record);
+                    }
+                    return Err(e).context("Failed to read chunk");
+                }
+            };
             temp_file.write_all(&chunk)
                 .context("Failed to write to temporary file")?;
+            hasher.update(&chunk);
 
             downloaded += chunk.len() as u64;
 
@@ -195,6 +458,18 @@ impl Downloader {
                 || downloaded == total_size;
 
             if should_report {
+                // 记录已写入的字节数，用于断点续传
+                temp_file.flush()
+                    .context("Failed to flush temporary file")?;
+                record.downloaded = downloaded;
+                Self::save_partial(&meta_path, &record)?;
+
+                // 服务器未返回大小时无法提前检查，下载过程中确认下载目录仍留有预留空间
+                if total_size == 0 {
+                    disk::ensure_available(&staging_dir, 0)
+                        .context(format!("Cannot download {} {}", candidate, version))?;
+                }
+
                 // 发送进度事件
                 let progress = DownloadProgress {
                     candidate: candidate.to_string(),
@@ -202,6 +477,7 @@ impl Downloader {
                     downloaded,
                     total: total_size,
                     percentage,
+                    resumed_from,
                 };
 
                 // 忽略发送错误（前端可能未监听）
@@ -224,75 +500,181 @@ impl Downloader {
         // 显式关闭文件句柄
         drop(temp_file);
 
+        record.downloaded = downloaded;
+        Self::save_partial(&meta_path, &record)?;
+
+        // 连接提前关闭时保留暂存文件，下次继续
+        if total_size > 0 && downloaded < total_size {
+            return Err(SdkError::IncompleteDownload { received: downloaded, expected: total_size }.into());
+        }
+
         // 验证下载的文件
-        let file_size = std::fs::metadata(&temp_file_path)
+        let file_size = fs::metadata(&part_path)
             .context("Failed to get file metadata")?
             .len();
 
-        println!("Download completed: {:?}", temp_file_path);
         println!("Downloaded file size: {} bytes (expected: {} bytes)", file_size, total_size);
 
         if file_size != downloaded {
-            anyhow::bail!("File size mismatch: downloaded {} bytes but file is {} bytes", downloaded, file_size);
+            Self::discard_partial(&meta_path, &part_path);
+            return Err(SdkError::IncompleteDownload { received: file_size, expected: downloaded })
+                .context("File size mismatch");
         }
 
-        Ok(temp_file_path)
-    }
+        // 6. 校验文件完整性；校验失败的文件无法续传，直接删除
+        let checksum = match hasher.finish() {
+            Ok(status) => status,
+            Err(e) => {
+                Self::discard_partial(&meta_path, &part_path);
+                return Err(e.context(format!("Refusing to install {} {}", candidate, version)));
+            }
+        };
 
-    /// 下载SDK（简化版，不报告进度）
-    ///
-    /// # Arguments
-    /// * `candidate` - SDK 候选者名称
-    /// * `version` - 版本号
-    ///
-    /// # Returns
-    /// 返回下载的临时文件路径
-    pub async fn download_sdk_simple(
-        &self,
-        candidate: &str,
-        version: &str,
-    ) -> Result<PathBuf> {
-        // 1. 获取下载URL
-        let platform = detect_platform();
-        let url = ApiEndpoints::download(candidate, version, &platform);
+        match &checksum {
+            ChecksumStatus::Verified { algorithm } => println!("✓ Checksum verified ({})", algorithm),
+            ChecksumStatus::Unverified => {
+                println!("✗ Archive is unverified: no checksum provided by server");
+                if self.require_checksum {
+                    Self::discard_partial(&meta_path, &part_path);
+                    return Err(SdkError::ChecksumMissing {
+                        candidate: candidate.to_string(),
+                        version: version.to_string(),
+                    }.into());
+                }
+            }
+        }
 
-        println!("Downloading SDK from: {}", url);
+        // 7. 下载完成，移动到最终文件名并删除元数据
+        let temp_file_path = staging_dir.join(format!("{}-{}.{}", candidate, version, file_extension));
+        fs::rename(&part_path, &temp_file_path)
+            .context("Failed to move completed download into place")?;
+        let _ = fs::remove_file(&meta_path);
 
-        // 2. 发送HTTP请求并下载
-        let response = self.client.get(&url)
-            .send()
-            .await
-            .context("Failed to send download request")?;
+        println!("Download completed: {:?}", temp_file_path);
 
-        // 检查响应状态
-        if !response.status().is_success() {
-            anyhow::bail!("Download failed with status: {}", response.status());
+        Ok(DownloadedArchive {
+            path: temp_file_path,
+            size: file_size,
+            checksum,
+        })
+    }
+}
+
+impl Default for Downloader {
+    fn default() -> Self {
+        Self::new().expect("Failed to create Downloader")
+    }
+}
+
+/// 根据响应头确定归档文件扩展名
+fn detect_file_extension(response: &Response, broker_archive_type: Option<&str>, candidate: &str) -> String {
+    // 优先使用 SDKMAN 的 X-Sdkman-ArchiveType 响应头（broker 重定向响应中的值优先）
+    let archive_type = broker_archive_type
+        .or_else(|| response.headers()
+            .get("x-sdkman-archivetype")
+            .and_then(|v| v.to_str().ok()))
+        .unwrap_or("");
+
+    let content_type = response.headers()
+        .get("content-type")
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or("");
+
+    // 检查 Content-Disposition 头中的文件名
+    let content_disposition = response.headers()
+        .get("content-disposition")
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or("");
+
+    // 根据多种因素确定文件扩展名
+    let file_extension = if !archive_type.is_empty() {
+        // 优先使用 SDKMAN 提供的类型
+        if archive_type == "zip" {
+            "zip"
+        } else if archive_type == "tar.gz" || archive_type == "tgz" {
+            "tar.gz"
+        } else {
+            archive_type  // 直接使用
+        }
+    } else if content_disposition.contains(".zip") {
+        "zip"
+    } else if content_disposition.contains(".tar.gz") || content_disposition.contains(".tgz") {
+        "tar.gz"
+    } else if content_type.contains("zip") || content_type.contains("application/x-zip") {
+        "zip"
+    } else if content_type.contains("gzip") || content_type.contains("x-gzip") {
+        "tar.gz"
+    } else {
+        // 某些 SDK（如 Gradle）默认为 zip
+        // 根据 candidate 类型判断
+        match candidate {
+            "gradle" | "maven" | "ant" => "zip",
+            _ => "tar.gz",  // JDK 等默认 tar.gz
         }
+    };
 
-        // 3. 创建临时文件
-        let temp_dir = std::env::temp_dir();
-        let temp_file_path = temp_dir.join(format!("{}-{}.zip", candidate, version));
+    println!("X-Sdkman-ArchiveType: {}", archive_type);
+    println!("Content-Type: {}", content_type);
+    println!("Content-Disposition: {}", content_disposition);
+    println!("Using file extension: {}", file_extension);
 
-        // 4. 下载文件
-        let bytes = response.bytes()
-            .await
-            .context("Failed to download file")?;
+    file_extension.to_string()
+}
 
-        // 5. 写入文件
-        let mut temp_file = File::create(&temp_file_path)
-            .context("Failed to create temporary file")?;
+/// 读取字符串形式的响应头
+fn header_string(response: &Response, name: HeaderName) -> Option<String> {
+    response.headers()
+        .get(name)
+        .and_then(|v| v.to_str().ok())
+        .map(|v| v.to_string())
+}
 
-        temp_file.write_all(&bytes)
-            .context("Failed to write to temporary file")?;
+/// 解析 Content-Range 响应头
+///
+/// 格式: `bytes <start>-<end>/<total>`，total 可能为 `*`
+///
+/// # Returns
+/// 返回 (起始位置, 文件总大小)
+fn parse_content_range(value: &str) -> Option<(u64, Option<u64>)> {
+    let range = value.trim().strip_prefix("bytes ")?;
+    let (span, total) = range.split_once('/')?;
+    let (start, _end) = span.split_once('-')?;
+
+    let start = start.trim().parse().ok()?;
+    let total = total.trim().parse().ok();
+
+    Some((start, total))
+}
 
-        println!("Download completed: {:?}", temp_file_path);
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-        Ok(temp_file_path)
+    #[test]
+    fn test_parse_content_range() {
+        assert_eq!(parse_content_range("bytes 100-199/200"), Some((100, Some(200))));
+        assert_eq!(parse_content_range("bytes 0-99/*"), Some((0, None)));
+        assert_eq!(parse_content_range("bytes */200"), None);
+        assert_eq!(parse_content_range("items 0-1/2"), None);
     }
-}
 
-impl Default for Downloader {
-    fn default() -> Self {
-        Self::new().expect("Failed to create Downloader")
+    #[test]
+    fn test_if_range_validator() {
+        let mut partial = PartialDownload {
+            url: "https://example.com/a".to_string(),
+            etag: Some("\"abc\"".to_string()),
+            last_modified: Some("Wed, 21 Oct 2015 07:28:00 GMT".to_string()),
+            downloaded: 10,
+            total: 100,
+            file_extension: "tar.gz".to_string(),
+        };
+        assert_eq!(partial.if_range_validator(), Some("\"abc\""));
+
+        // 弱 ETag 不能用于 If-Range，回退到 Last-Modified
+        partial.etag = Some("W/\"abc\"".to_string());
+        assert_eq!(partial.if_range_validator(), Some("Wed, 21 Oct 2015 07:28:00 GMT"));
+
+        partial.last_modified = None;
+        assert_eq!(partial.if_range_validator(), None);
     }
 }
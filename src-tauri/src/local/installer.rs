@@ -1,11 +1,18 @@
-use std::path::{Path, PathBuf};
+use std::path::{Component, Path, PathBuf};
 use std::fs;
+use std::io::{Read, Seek, SeekFrom};
 use anyhow::{Result, Context};
+use super::paths;
+use super::lock::{CandidateState, SdkmanLock};
+use super::disk;
 use serde::{Deserialize, Serialize};
 use tauri::{Emitter, AppHandle};
 use zip::ZipArchive;
 use flate2::read::GzDecoder;
 use tar::Archive;
+use tokio_util::sync::CancellationToken;
+use crate::error::SdkError;
+use crate::jobs::{self, Cancelled};
 
 /// 安装完成事件
 #[derive(Clone, Serialize, Deserialize, Debug)]
@@ -36,30 +43,54 @@ pub struct UninstallComplete {
     pub message: Option<String>,
 }
 
+/// 解压结果
+#[derive(Debug, Clone, Copy)]
+struct Extracted {
+    /// 归档是否记录了 Unix 权限（tar 总是记录；ZIP 只有在 Unix 系统上创建的条目才记录，
+    /// Windows 上创建的条目只有 DOS 属性）
+    has_unix_modes: bool,
+    /// 写入的文件数（含链接，不含目录）
+    files: usize,
+    /// 归档是否在安装根目录下带有 `bin/` 中的文件
+    has_bin: bool,
+}
+
+/// 安装根目录中必须存在的入口（任一存在即可，未列出的候选者不检查）
+///
+/// macOS 的 JDK 归档保留 `Contents/Home` 结构
+fn expected_entries(candidate: &str) -> &'static [&'static str] {
+    match candidate {
+        "java" => &["bin/java", "bin/java.exe", "Contents/Home/bin/java"],
+        _ => &[],
+    }
+}
+
 /// SDK 安装器
 pub struct Installer;
 
 impl Installer {
-    /// 获取 SDKMAN 根目录路径
+    /// 获取 SDKMAN 根目录路径（见 [`paths::sdkman_dir`]）
     fn get_sdkman_dir() -> Result<PathBuf> {
-        let home = dirs::home_dir()
-            .context("Failed to get home directory")?;
-        Ok(home.join(".sdkman"))
+        paths::sdkman_dir()
     }
 
     /// 获取候选者目录路径
     fn get_candidate_dir(candidate: &str) -> Result<PathBuf> {
-        let sdkman_dir = Self::get_sdkman_dir()?;
-        Ok(sdkman_dir.join("candidates").join(candidate))
+        paths::candidate_dir(candidate)
     }
 
     /// 从归档文件安装SDK（自动检测ZIP或tar.gz）
     ///
+    /// 先解压到同级的临时目录 `.staging-<版本>-<ID>`，验证通过后再重命名到
+    /// `candidates/<候选者>/<版本>`；重新安装时旧版本保留到新版本就绪为止。
+    /// 解压期间不持有 [`SdkmanLock`]，只在替换时加锁
+    ///
     /// # Arguments
     /// * `archive_path` - 归档文件路径（ZIP或tar.gz）
     /// * `candidate` - SDK候选者名称
     /// * `version` - 版本号
     /// * `app` - Tauri应用句柄（用于发送进度事件）
+    /// * `cancel` - 取消令牌；取消时删除已解压的部分并返回 [`Cancelled`] 错误
     ///
     /// # Returns
     /// 返回安装路径
@@ -68,44 +99,96 @@ impl Installer {
         candidate: &str,
         version: &str,
         app: AppHandle,
+        cancel: &CancellationToken,
     ) -> Result<PathBuf> {
         println!("Installing {} {} from {:?}", candidate, version, archive_path);
 
-        // 1. 创建目标目录
-        let install_dir = Self::get_candidate_dir(candidate)?
-            .join(version);
+        // 1. 检查磁盘空间（重新安装时旧版本保留到最后，同样需要完整的空间）
+        //    解压只写入候选者目录下的临时目录，归档所在的下载目录只读取，不需要检查
+        let candidate_dir = Self::get_candidate_dir(candidate)?;
+        let install_dir = candidate_dir.join(version);
 
-        // 如果目标目录已存在，先删除
-        if install_dir.exists() {
-            println!("Removing existing installation at {:?}", install_dir);
-            fs::remove_dir_all(&install_dir)
-                .context("Failed to remove existing installation")?;
-        }
+        let estimated_size = disk::estimate_uncompressed_size(archive_path)?;
+        println!("Estimated installation size: {} bytes", estimated_size);
+        disk::ensure_available(&candidate_dir, estimated_size)
+            .context(format!("Cannot install {} {}", candidate, version))?;
 
-        // 创建目标目录
-        fs::create_dir_all(&install_dir)
-            .context("Failed to create installation directory")?;
+        // 2. 创建临时解压目录
+        //    与版本目录位于同一目录下，因此在同一卷上：上面的空间检查针对该卷，最后的替换也依赖同卷 rename
+        let staging_dir = candidate_dir.join(format!("{}{}-{}", STAGING_PREFIX, version, unique_suffix()));
 
-        // 2. 根据文件扩展名选择解压方法
+        fs::create_dir_all(&staging_dir)
+            .context("Failed to create staging directory")?;
+        let before = CandidateState::capture(&candidate_dir);
+
+        // 3. 根据文件扩展名选择解压方法
         let file_name = archive_path.file_name()
             .and_then(|n| n.to_str())
             .unwrap_or("");
 
-        if file_name.ends_with(".tar.gz") || file_name.ends_with(".tgz") {
-            Self::extract_tar_gz(archive_path, &install_dir, candidate, version, app.clone())?;
+        let mut report_progress = |current: usize, total: usize| {
+            let percentage = if total > 0 {
+                (current as f64 / total as f64) * 100.0
+            } else {
+                0.0 // tar.gz无法提前知道总数
+            };
+            let progress = InstallProgress {
+                candidate: candidate.to_string(),
+                version: version.to_string(),
+                current,
+                total,
+                percentage,
+            };
+            let _ = app.emit("install-progress", &progress);
+        };
+
+        let extracted = if file_name.ends_with(".tar.gz") || file_name.ends_with(".tgz") {
+            Self::extract_tar_gz(archive_path, &staging_dir, cancel, &mut report_progress)
         } else if file_name.ends_with(".zip") {
-            Self::extract_zip(archive_path, &install_dir, candidate, version, app.clone())?;
+            Self::extract_zip(archive_path, &staging_dir, cancel, &mut report_progress)
         } else {
-            anyhow::bail!("Unsupported archive format: {}", file_name);
-        }
+            Err(SdkError::invalid_archive(format!("Unsupported archive format: {}", file_name)).into())
+        };
 
-        // 3. 设置权限（Unix系统）
-        #[cfg(unix)]
-        Self::set_executable_permissions(&install_dir)?;
+        // 4. 归档没有记录权限时（Unix 系统）为 bin/ 下的文件添加可执行权限，并验证解压结果
+        let prepared = extracted.and_then(|extracted| {
+            #[cfg(unix)]
+            if !extracted.has_unix_modes {
+                Self::set_executable_permissions(&staging_dir)?;
+            }
+
+            Self::validate_staging(&staging_dir, candidate, &extracted)
+        });
+
+        // 5. 加锁后替换到最终位置（失败时原有版本保持不变）
+        let installed = prepared.and_then(|_| {
+            if cancel.is_cancelled() {
+                return Err(Cancelled.into());
+            }
+            let _lock = SdkmanLock::acquire(&format!("install {} {}", candidate, version))?;
+
+            // 解压期间目录可能已被 sdk 命令行修改（如同一版本已被安装或 current 被切换），
+            // 此时不覆盖对方的结果
+            before.ensure_version_unchanged(&candidate_dir, candidate, version)?;
+            replace_installation(&staging_dir, &install_dir)
+        });
+
+        // 取消或失败时清理临时目录，不会留下不完整的版本目录
+        if let Err(e) = installed {
+            if jobs::is_cancelled(&e) {
+                println!("Installation of {} {} cancelled, removing {:?}", candidate, version, staging_dir);
+            } else {
+                println!("Installation of {} {} failed, removing {:?}", candidate, version, staging_dir);
+            }
+            if let Err(remove_err) = fs::remove_dir_all(&staging_dir) {
+                eprintln!("Warning: Failed to remove staging directory {:?}: {}", staging_dir, remove_err);
+            }
+            return Err(e);
+        }
 
         println!("Installation completed at {:?}", install_dir);
 
-        // 4. 发送完成事件
+        // 6. 发送完成事件
         let complete = InstallComplete {
             candidate: candidate.to_string(),
             version: version.to_string(),
@@ -119,6 +202,56 @@ impl Installer {
         Ok(install_dir)
     }
 
+    /// 验证临时目录中的解压结果
+    ///
+    /// 安装根目录必须是目录且包含文件；归档带有 `bin/` 时该目录必须存在，
+    /// 已知候选者（如 java）还需包含对应的可执行文件
+    fn validate_staging(staging_dir: &Path, candidate: &str, extracted: &Extracted) -> Result<()> {
+        let is_dir = staging_dir.symlink_metadata()
+            .context(format!("Failed to read staging directory: {:?}", staging_dir))?
+            .is_dir();
+        if !is_dir {
+            return Err(SdkError::invalid_archive("Installation root is not a directory").into());
+        }
+
+        let has_files = fs::read_dir(staging_dir)
+            .context(format!("Failed to read staging directory: {:?}", staging_dir))?
+            .next()
+            .is_some();
+        if !has_files || extracted.files == 0 {
+            return Err(SdkError::invalid_archive("Archive did not contain any files").into());
+        }
+
+        if extracted.has_bin && !staging_dir.join("bin").is_dir() {
+            return Err(SdkError::invalid_archive("Archive contains bin/ but it was not extracted as a directory").into());
+        }
+
+        let expected = expected_entries(candidate);
+        if !expected.is_empty() && !expected.iter().any(|entry| staging_dir.join(entry).is_file()) {
+            return Err(SdkError::invalid_archive(format!(
+                "Archive does not look like {}: none of {} found",
+                candidate,
+                expected.join(", ")
+            )).into());
+        }
+
+        Ok(())
+    }
+
+    /// 清理上次异常退出时遗留的临时目录
+    ///
+    /// 删除所有 `.staging-*` 目录；如果 `.backup-*` 对应的版本目录不存在
+    /// （在替换过程中崩溃），则恢复备份，否则删除备份。
+    /// 启动时界面已经可以开始安装，名称中带有当前进程 ID 的目录属于正在进行的安装，跳过
+    ///
+    /// # Returns
+    /// 返回处理的目录数量
+    pub fn cleanup_stale_staging() -> Result<usize> {
+        let candidates_dir = Self::get_sdkman_dir()?.join("candidates");
+        let _lock = SdkmanLock::acquire("clean up stale installation directories")?;
+        cleanup_stale_in(&candidates_dir, std::process::id())
+    }
+
     /// 从ZIP文件安装SDK（保留用于兼容性）
     ///
     /// # Arguments
@@ -126,6 +259,7 @@ impl Installer {
     /// * `candidate` - SDK候选者名称
     /// * `version` - 版本号
     /// * `app` - Tauri应用句柄（用于发送进度事件）
+    /// * `cancel` - 取消令牌
     ///
     /// # Returns
     /// 返回安装路径
@@ -134,19 +268,22 @@ impl Installer {
         candidate: &str,
         version: &str,
         app: AppHandle,
+        cancel: &CancellationToken,
     ) -> Result<PathBuf> {
         // 直接调用新的通用方法
-        Self::install_from_archive(zip_path, candidate, version, app)
+        Self::install_from_archive(zip_path, candidate, version, app, cancel)
     }
 
     /// 解压tar.gz文件
+    ///
+    /// 拒绝任何会写到 `target_dir` 之外的条目（`..`、绝对路径、指向外部的符号链接或硬链接），
+    /// 一旦发现即中止整个解压
     fn extract_tar_gz(
         tar_gz_path: &Path,
         target_dir: &Path,
-        candidate: &str,
-        version: &str,
-        app: AppHandle,
-    ) -> Result<()> {
+        cancel: &CancellationToken,
+        on_progress: &mut dyn FnMut(usize, usize),
+    ) -> Result<Extracted> {
         // 验证文件是否存在
         if !tar_gz_path.exists() {
             anyhow::bail!("tar.gz file does not exist: {:?}", tar_gz_path);
@@ -160,25 +297,24 @@ impl Installer {
         println!("Opening tar.gz file: {:?} (size: {} bytes)", tar_gz_path, file_size);
 
         if file_size == 0 {
-            anyhow::bail!("tar.gz file is empty: {:?}", tar_gz_path);
+            return Err(SdkError::invalid_archive(format!("tar.gz file is empty: {:?}", tar_gz_path)).into());
         }
 
         let file = fs::File::open(tar_gz_path)
             .context(format!("Failed to open tar.gz file: {:?}", tar_gz_path))?;
 
         // 验证 gzip 文件头（前两个字节应该是 0x1f 0x8b）
-        use std::io::Read;
         let mut file_for_check = fs::File::open(tar_gz_path)?;
         let mut magic_bytes = [0u8; 2];
         file_for_check.read_exact(&mut magic_bytes)
             .context("Failed to read file header")?;
 
         if magic_bytes != [0x1f, 0x8b] {
-            anyhow::bail!(
+            return Err(SdkError::invalid_archive(format!(
                 "File is not a valid gzip archive (magic bytes: {:02x} {:02x}). \
                 This might be a ZIP file or corrupted download. Try redownloading.",
                 magic_bytes[0], magic_bytes[1]
-            );
+            )).into());
         }
 
         println!("Verified gzip format (magic bytes: 1f 8b)");
@@ -195,60 +331,88 @@ impl Installer {
             .context("Failed to read tar.gz entries. The file may be corrupted or not a valid tar archive.")?;
 
         let mut file_count = 0;
+        let mut has_bin = false;
         for (i, entry_result) in entries.enumerate() {
+            if cancel.is_cancelled() {
+                return Err(Cancelled.into());
+            }
+
             let mut entry = entry_result
                 .context(format!("Failed to read tar entry at index {}. The archive may be corrupted.", i))?;
 
             let path = entry.path()
-                .context("Failed to get entry path")?;
+                .context("Failed to get entry path")?
+                .into_owned();
 
             // 跳过顶层目录，只提取内容
-            let components: Vec<_> = path.components().collect();
-            if components.len() <= 1 {
-                continue; // 跳过顶层目录本身
-            }
-
-            // 移除顶层目录，构建目标路径
-            let relative_path = components[1..].iter()
-                .collect::<PathBuf>();
-            let outpath = target_dir.join(relative_path);
+            let relative_path = match strip_top_level(&path)? {
+                Some(relative_path) => relative_path,
+                None => continue, // 跳过顶层目录本身
+            };
 
-            // 创建父目录
-            if let Some(parent) = outpath.parent() {
-                fs::create_dir_all(parent)?;
+            let entry_type = entry.header().entry_type();
+            if entry_type.is_dir() {
+                let outpath = resolve_within(target_dir, target_dir, &relative_path, 0)?;
+                fs::create_dir_all(&outpath)
+                    .context(format!("Failed to create directory: {:?}", outpath))?;
+            } else if entry_type.is_symlink() {
+                let link_target = entry.link_name()
+                    .context("Failed to get symlink target")?
+                    .context(format!("Symlink without target: {:?}", path))?
+                    .into_owned();
+                let outpath = prepare_output_path(target_dir, &relative_path)?;
+                ensure_link_within(target_dir, &outpath, &link_target)
+                    .context(format!("Refusing to extract symlink {:?}", path))?;
+                entry.unpack(&outpath)
+                    .context(format!("Failed to extract symlink: {:?}", outpath))?;
+            } else if entry_type.is_hard_link() {
+                // 硬链接目标是归档内的路径，同样需要去掉顶层目录
+                let link_name = entry.link_name()
+                    .context("Failed to get hard link target")?
+                    .context(format!("Hard link without target: {:?}", path))?
+                    .into_owned();
+                let source = strip_top_level(&link_name)?
+                    .context(format!("Hard link {:?} points outside the installation directory", path))?;
+                let source = resolve_within(target_dir, target_dir, &source, 0)
+                    .context(format!("Refusing to extract hard link {:?}", path))?;
+                let outpath = prepare_output_path(target_dir, &relative_path)?;
+                fs::hard_link(&source, &outpath)
+                    .context(format!("Failed to create hard link: {:?}", outpath))?;
+            } else if entry_type.is_file() || entry_type.is_contiguous() || entry_type.is_gnu_sparse() {
+                let outpath = prepare_output_path(target_dir, &relative_path)?;
+                entry.unpack(&outpath)
+                    .context(format!("Failed to extract file: {:?}", outpath))?;
+            } else {
+                // 设备文件、FIFO 等不属于 SDK 内容，直接跳过
+                println!("Skipping unsupported tar entry {:?} ({:?})", path, entry_type);
+                continue;
             }
 
-            // 解压文件
-            entry.unpack(&outpath)
-                .context(format!("Failed to extract file: {:?}", outpath))?;
-
             file_count += 1;
+            has_bin |= relative_path.starts_with("bin");
 
-            // 每100个文件发送一次进度（tar.gz没有总数，所以只报告已处理数量）
+            // 每100个文件报告一次进度（tar.gz没有总数，所以只报告已处理数量）
             if file_count % 100 == 0 {
-                let progress = InstallProgress {
-                    candidate: candidate.to_string(),
-                    version: version.to_string(),
-                    current: file_count,
-                    total: 0, // tar.gz无法提前知道总数
-                    percentage: 0.0,
-                };
-                let _ = app.emit("install-progress", &progress);
+                on_progress(file_count, 0);
             }
         }
 
         println!("Extracted {} files", file_count);
-        Ok(())
+        verify_symlinks(target_dir)?;
+        // tar 条目总是带有权限，entry.unpack 已按归档设置
+        Ok(Extracted { has_unix_modes: true, files: file_count, has_bin })
     }
 
     /// 解压ZIP文件
+    ///
+    /// 与 tar.gz 相同，拒绝任何会写到 `target_dir` 之外的条目；
+    /// 还原条目记录的 Unix 权限，符号链接条目创建为真正的符号链接
     fn extract_zip(
         zip_path: &Path,
         target_dir: &Path,
-        candidate: &str,
-        version: &str,
-        app: AppHandle,
-    ) -> Result<()> {
+        cancel: &CancellationToken,
+        on_progress: &mut dyn FnMut(usize, usize),
+    ) -> Result<Extracted> {
         // 验证ZIP文件是否存在
         if !zip_path.exists() {
             anyhow::bail!("ZIP file does not exist: {:?}", zip_path);
@@ -262,7 +426,7 @@ impl Installer {
         println!("Opening ZIP file: {:?} (size: {} bytes)", zip_path, file_size);
 
         if file_size == 0 {
-            anyhow::bail!("ZIP file is empty: {:?}", zip_path);
+            return Err(SdkError::invalid_archive(format!("ZIP file is empty: {:?}", zip_path)).into());
         }
 
         let file = fs::File::open(zip_path)
@@ -271,71 +435,110 @@ impl Installer {
         let mut archive = ZipArchive::new(file)
             .context(format!("Failed to read ZIP archive: {:?}. The file may be corrupted or not a valid ZIP file.", zip_path))?;
 
+        // 单独的句柄用于读取中央目录中的 "version made by"
+        let mut raw = fs::File::open(zip_path)
+            .context(format!("Failed to open ZIP file: {:?}", zip_path))?;
+
         let total_files = archive.len();
         println!("Extracting {} files...", total_files);
 
+        let mut has_unix_modes = false;
+        let mut file_count = 0;
+        let mut has_bin = false;
+        // 目录权限在所有条目解压完成后再设置，避免只读目录阻止写入其中的文件
+        let mut dir_modes = Vec::new();
+
         for i in 0..total_files {
+            if cancel.is_cancelled() {
+                return Err(Cancelled.into());
+            }
+
             let mut file = archive.by_index(i)
                 .context(format!("Failed to read file at index {}", i))?;
 
-            let path = match file.enclosed_name() {
-                Some(path) => path.to_path_buf(),
-                None => continue,
-            };
+            // enclosed_name 会拒绝 `..` 和绝对路径
+            let path = file.enclosed_name()
+                .context(format!("Archive entry escapes the installation directory: {}", file.name()))?;
 
             // 跳过顶层目录，只提取内容（与tar.gz逻辑一致）
-            let components: Vec<_> = path.components().collect();
-            if components.len() <= 1 {
-                continue; // 跳过顶层目录本身
-            }
+            let relative_path = match strip_top_level(&path)? {
+                Some(relative_path) => relative_path,
+                None => continue, // 跳过顶层目录本身
+            };
 
-            // 移除顶层目录，构建目标路径
-            let relative_path: PathBuf = components[1..].iter().collect();
-            let outpath = target_dir.join(relative_path);
+            // zip 库会为 DOS/Windows 创建的条目编造 0664 权限，只信任 Unix 上创建的条目
+            let mode = if made_on_unix(&mut raw, file.central_header_start())? {
+                file.unix_mode()
+            } else {
+                None
+            };
+            has_unix_modes |= mode.is_some();
 
-            if file.name().ends_with('/') {
+            if file.is_dir() {
                 // 这是一个目录
+                let outpath = resolve_within(target_dir, target_dir, &relative_path, 0)?;
                 fs::create_dir_all(&outpath)
                     .context(format!("Failed to create directory: {:?}", outpath))?;
+                if let Some(mode) = mode {
+                    dir_modes.push((outpath, mode));
+                }
+            } else if file.is_symlink() {
+                // 符号链接的内容是链接目标
+                let outpath = prepare_output_path(target_dir, &relative_path)?;
+                let mut link_target = String::new();
+                file.read_to_string(&mut link_target)
+                    .context(format!("Failed to read symlink target: {:?}", path))?;
+                let link_target = PathBuf::from(link_target);
+                ensure_link_within(target_dir, &outpath, &link_target)
+                    .context(format!("Refusing to extract symlink {:?}", path))?;
+                create_symlink(&link_target, &outpath)
+                    .context(format!("Failed to create symlink: {:?}", outpath))?;
             } else {
                 // 这是一个文件
-                if let Some(parent) = outpath.parent() {
-                    if !parent.exists() {
-                        fs::create_dir_all(parent)
-                            .context(format!("Failed to create parent directory: {:?}", parent))?;
-                    }
-                }
-
+                let outpath = prepare_output_path(target_dir, &relative_path)?;
                 let mut outfile = fs::File::create(&outpath)
                     .context(format!("Failed to create file: {:?}", outpath))?;
 
                 std::io::copy(&mut file, &mut outfile)
                     .context(format!("Failed to write file: {:?}", outpath))?;
+
+                if let Some(mode) = mode {
+                    set_unix_mode(&outpath, mode, false)?;
+                }
             }
 
-            // 发送进度事件
-            if i % 10 == 0 || i == total_files - 1 {
-                let percentage = ((i + 1) as f64 / total_files as f64) * 100.0;
-                let progress = InstallProgress {
-                    candidate: candidate.to_string(),
-                    version: version.to_string(),
-                    current: i + 1,
-                    total: total_files,
-                    percentage,
-                };
+            if !file.is_dir() {
+                file_count += 1;
+                has_bin |= relative_path.starts_with("bin");
+            }
 
-                let _ = app.emit("install-progress", &progress);
+            // 报告进度
+            if i % 10 == 0 || i == total_files - 1 {
+                on_progress(i + 1, total_files);
 
                 if i % 100 == 0 || i == total_files - 1 {
+                    let percentage = ((i + 1) as f64 / total_files as f64) * 100.0;
                     println!("Extracted {} / {} files ({:.2}%)", i + 1, total_files, percentage);
                 }
             }
         }
 
-        Ok(())
+        // 先设置子目录，再设置上级目录
+        for (dir, mode) in dir_modes.iter().rev() {
+            set_unix_mode(dir, *mode, true)?;
+        }
+
+        if !has_unix_modes {
+            println!("ZIP archive carries no Unix permissions");
+        }
+
+        verify_symlinks(target_dir)?;
+        Ok(Extracted { has_unix_modes, files: file_count, has_bin })
     }
 
     /// 设置可执行权限（Unix系统）
+    ///
+    /// 仅在归档没有记录 Unix 权限时使用：为 `bin/` 下的文件统一设置 755
     #[cfg(unix)]
     fn set_executable_permissions(install_dir: &Path) -> Result<()> {
         use std::os::unix::fs::PermissionsExt;
@@ -378,16 +581,21 @@ impl Installer {
         println!("Uninstalling {} {}", candidate, version);
 
         // 1. 获取安装目录
-        let install_dir = Self::get_candidate_dir(candidate)?
-            .join(version);
+        let candidate_dir = Self::get_candidate_dir(candidate)?;
+        let install_dir = candidate_dir.join(version);
 
-        // 检查目录是否存在
-        if !install_dir.exists() {
-            anyhow::bail!("Version {} of {} is not installed", version, candidate);
+        // 加锁后再检查目录，避免与 sdk 命令行或其他窗口同时修改
+        let before = CandidateState::capture(&candidate_dir);
+        let _lock = SdkmanLock::acquire(&format!("uninstall {} {}", candidate, version))?;
+        before.ensure_version_unchanged(&candidate_dir, candidate, version)?;
+
+        // 检查目录是否存在（损坏的本地链接同样可以删除）
+        if install_dir.symlink_metadata().is_err() {
+            return Err(SdkError::not_installed(candidate, version).into());
         }
 
-        // 2. 删除目录
-        fs::remove_dir_all(&install_dir)
+        // 2. 删除目录；本地链接版本只删除链接，不删除链接指向的目录
+        remove_path(&install_dir)
             .context(format!("Failed to remove installation directory: {:?}", install_dir))?;
 
         println!("Removed installation directory: {:?}", install_dir);
@@ -458,3 +666,622 @@ impl Installer {
         Ok(has_files)
     }
 }
+
+/// 临时解压目录前缀（`.staging-<版本>-<ID>`）
+const STAGING_PREFIX: &str = ".staging-";
+/// 重新安装时旧版本的备份目录前缀（`.backup-<版本>-<ID>`）
+const BACKUP_PREFIX: &str = ".backup-";
+
+/// 生成临时目录名后缀（进程ID + 时间戳），避免并发安装冲突
+fn unique_suffix() -> String {
+    let nanos = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_nanos())
+        .unwrap_or(0);
+    format!("{}-{}", std::process::id(), nanos)
+}
+
+/// 用临时目录替换安装目录
+///
+/// 已有版本先重命名为备份，新版本就位后再删除备份；
+/// 新版本重命名失败时恢复备份
+fn replace_installation(staging_dir: &Path, install_dir: &Path) -> Result<()> {
+    let parent = install_dir.parent()
+        .context(format!("Invalid installation directory: {:?}", install_dir))?;
+    let version = install_dir.file_name()
+        .context(format!("Invalid installation directory: {:?}", install_dir))?
+        .to_string_lossy();
+
+    let backup_dir = if install_dir.symlink_metadata().is_ok() {
+        let backup_dir = parent.join(format!("{}{}-{}", BACKUP_PREFIX, version, unique_suffix()));
+        println!("Moving existing installation {:?} to {:?}", install_dir, backup_dir);
+        fs::rename(install_dir, &backup_dir)
+            .context("Failed to move existing installation aside")?;
+        Some(backup_dir)
+    } else {
+        None
+    };
+
+    if let Err(e) = fs::rename(staging_dir, install_dir) {
+        if let Some(backup_dir) = &backup_dir {
+            if let Err(restore_err) = fs::rename(backup_dir, install_dir) {
+                eprintln!("Warning: Failed to restore previous installation from {:?}: {}", backup_dir, restore_err);
+            }
+        }
+        return Err(e).context(format!("Failed to move staging directory to {:?}", install_dir));
+    }
+
+    if let Some(backup_dir) = backup_dir {
+        if let Err(e) = remove_path(&backup_dir) {
+            eprintln!("Warning: Failed to remove previous installation {:?}: {}", backup_dir, e);
+        }
+    }
+
+    Ok(())
+}
+
+/// 删除目录或符号链接（不跟随链接）
+fn remove_path(path: &Path) -> std::io::Result<()> {
+    if path.symlink_metadata()?.is_dir() {
+        fs::remove_dir_all(path)
+    } else {
+        fs::remove_file(path)
+    }
+}
+
+/// 从临时目录名的 `-<进程ID>-<时间戳>` 后缀中解析进程ID
+fn suffix_pid(name: &str) -> Option<u32> {
+    let mut parts = name.rsplitn(3, '-');
+    let _nanos = parts.next()?;
+    parts.next()?.parse().ok()
+}
+
+/// 从备份目录名中解析版本号（去掉前缀和 `-<进程ID>-<时间戳>` 后缀）
+fn backup_version(name: &str) -> Option<&str> {
+    let rest = name.strip_prefix(BACKUP_PREFIX)?;
+    let mut parts = rest.rsplitn(3, '-');
+    let _nanos = parts.next()?;
+    let _pid = parts.next()?;
+    parts.next().filter(|v| !v.is_empty())
+}
+
+/// 清理 `candidates_dir` 下所有候选者目录中遗留的临时目录
+fn cleanup_stale_in(candidates_dir: &Path, current_pid: u32) -> Result<usize> {
+    if !candidates_dir.exists() {
+        return Ok(0);
+    }
+
+    let mut cleaned = 0;
+    for candidate_entry in fs::read_dir(candidates_dir)
+        .context(format!("Failed to read directory: {:?}", candidates_dir))?
+    {
+        let candidate_dir = candidate_entry?.path();
+        if !candidate_dir.is_dir() {
+            continue;
+        }
+
+        let entries = match fs::read_dir(&candidate_dir) {
+            Ok(entries) => entries,
+            Err(e) => {
+                eprintln!("Warning: Failed to read directory {:?}: {}", candidate_dir, e);
+                continue;
+            }
+        };
+
+        for entry in entries {
+            let path = entry?.path();
+            let name = match path.file_name() {
+                Some(name) => name.to_string_lossy().to_string(),
+                None => continue,
+            };
+
+            if suffix_pid(&name) == Some(current_pid) {
+                continue;
+            }
+
+            let result = if name.starts_with(STAGING_PREFIX) {
+                println!("Removing stale staging directory {:?}", path);
+                remove_path(&path)
+            } else if let Some(version) = backup_version(&name) {
+                let install_dir = candidate_dir.join(version);
+                if install_dir.symlink_metadata().is_err() {
+                    println!("Restoring interrupted re-install from {:?}", path);
+                    fs::rename(&path, &install_dir)
+                } else {
+                    println!("Removing stale backup directory {:?}", path);
+                    remove_path(&path)
+                }
+            } else {
+                continue;
+            };
+
+            match result {
+                Ok(()) => cleaned += 1,
+                Err(e) => eprintln!("Warning: Failed to clean up {:?}: {}", path, e),
+            }
+        }
+    }
+
+    Ok(cleaned)
+}
+
+/// 解析符号链接时允许的最大嵌套层数
+const MAX_SYMLINK_DEPTH: usize = 40;
+
+/// 去掉归档条目的顶层目录（如 `jdk-25.0.1+8/`）
+///
+/// 包含 `..`、根目录或盘符前缀的条目会被拒绝；
+/// 返回 `None` 表示该条目就是顶层目录本身
+fn strip_top_level(path: &Path) -> Result<Option<PathBuf>> {
+    let mut parts = Vec::new();
+    for component in path.components() {
+        match component {
+            Component::Normal(part) => parts.push(part),
+            Component::CurDir => {}
+            Component::ParentDir | Component::RootDir | Component::Prefix(_) => {
+                return Err(SdkError::invalid_archive(format!("Archive entry escapes the installation directory: {:?}", path)).into());
+            }
+        }
+    }
+
+    if parts.len() <= 1 {
+        return Ok(None);
+    }
+
+    Ok(Some(parts[1..].iter().collect()))
+}
+
+/// 在 `root` 内从 `base` 出发解析 `path`
+///
+/// 按文件系统语义跟随已解压出的符号链接，任何一步离开 `root` 都返回错误，
+/// 因此 `a -> .` 再配合 `b -> a/..` 这类组合也会被识别
+fn resolve_within(root: &Path, base: &Path, path: &Path, depth: usize) -> Result<PathBuf> {
+    if depth > MAX_SYMLINK_DEPTH {
+        return Err(SdkError::invalid_archive(format!("Too many levels of symbolic links: {:?}", path)).into());
+    }
+
+    let mut current = base.to_path_buf();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                if current == root || !current.pop() || !current.starts_with(root) {
+                    return Err(SdkError::invalid_archive(format!("Path escapes the installation directory: {:?}", path)).into());
+                }
+            }
+            Component::Normal(part) => {
+                let next = current.join(part);
+                match fs::symlink_metadata(&next) {
+                    Ok(metadata) if metadata.file_type().is_symlink() => {
+                        let link_target = fs::read_link(&next)
+                            .context(format!("Failed to read symlink: {:?}", next))?;
+                        current = resolve_within(root, &current, &link_target, depth + 1)?;
+                    }
+                    _ => current = next,
+                }
+            }
+            Component::RootDir | Component::Prefix(_) => {
+                return Err(SdkError::invalid_archive(format!("Absolute path is not allowed: {:?}", path)).into());
+            }
+        }
+    }
+
+    Ok(current)
+}
+
+/// 计算条目的实际写入位置并创建父目录
+///
+/// 父目录经过 [`resolve_within`] 解析，保证不会通过已存在的符号链接写到外部；
+/// 如果目标位置已经是符号链接，先删除它而不是跟随写入
+fn prepare_output_path(root: &Path, relative_path: &Path) -> Result<PathBuf> {
+    let file_name = relative_path.file_name()
+        .context(format!("Invalid archive entry: {:?}", relative_path))?;
+    let parent = match relative_path.parent() {
+        Some(parent) => resolve_within(root, root, parent, 0)?,
+        None => root.to_path_buf(),
+    };
+
+    fs::create_dir_all(&parent)
+        .context(format!("Failed to create parent directory: {:?}", parent))?;
+
+    let outpath = parent.join(file_name);
+    if let Ok(metadata) = fs::symlink_metadata(&outpath) {
+        if !metadata.is_dir() {
+            fs::remove_file(&outpath)
+                .context(format!("Failed to replace existing file: {:?}", outpath))?;
+        }
+    }
+
+    Ok(outpath)
+}
+
+/// ZIP 条目是否在 Unix 系统上创建（中央目录头中 "version made by" 的高字节为 3）
+fn made_on_unix(archive: &mut fs::File, central_header_start: u64) -> Result<bool> {
+    let mut version_made_by = [0u8; 2];
+    archive.seek(SeekFrom::Start(central_header_start + 4))
+        .and_then(|_| archive.read_exact(&mut version_made_by))
+        .context("Failed to read ZIP central directory header")?;
+    Ok(version_made_by[1] == 3)
+}
+
+/// 按归档记录的权限设置文件或目录（去掉 setuid/setgid/sticky 位）
+///
+/// 保证所有者可读（目录可读写、可进入），避免之后无法卸载
+#[cfg(unix)]
+fn set_unix_mode(path: &Path, mode: u32, is_dir: bool) -> Result<()> {
+    use std::os::unix::fs::PermissionsExt;
+
+    let owner = if is_dir { 0o700 } else { 0o400 };
+    let mode = (mode & 0o777) | owner;
+    fs::set_permissions(path, fs::Permissions::from_mode(mode))
+        .context(format!("Failed to set permissions on {:?}", path))
+}
+
+#[cfg(not(unix))]
+fn set_unix_mode(_path: &Path, _mode: u32, _is_dir: bool) -> Result<()> {
+    Ok(())
+}
+
+/// 创建符号链接（调用前已通过 [`ensure_link_within`] 检查）
+#[cfg(unix)]
+fn create_symlink(link_target: &Path, link_path: &Path) -> std::io::Result<()> {
+    std::os::unix::fs::symlink(link_target, link_path)
+}
+
+/// 创建符号链接；没有创建权限时（未开启开发者模式）写入链接目标文本
+#[cfg(windows)]
+fn create_symlink(link_target: &Path, link_path: &Path) -> std::io::Result<()> {
+    let resolved = link_path.parent().map(|p| p.join(link_target));
+    let created = if resolved.is_some_and(|p| p.is_dir()) {
+        std::os::windows::fs::symlink_dir(link_target, link_path)
+    } else {
+        std::os::windows::fs::symlink_file(link_target, link_path)
+    };
+
+    created.or_else(|e| {
+        eprintln!("Warning: Failed to create symlink {:?}: {}, writing link target instead", link_path, e);
+        fs::write(link_path, link_target.to_string_lossy().as_bytes())
+    })
+}
+
+/// 解压完成后按最终的目录树重新检查所有符号链接
+///
+/// 创建链接时的检查基于当时的目录树，之后的条目可能创建或替换其经过的链接
+/// （如先 `x -> y/..` 再 `y -> .`）
+fn verify_symlinks(root: &Path) -> Result<()> {
+    let mut pending = vec![root.to_path_buf()];
+
+    while let Some(dir) = pending.pop() {
+        let entries = fs::read_dir(&dir)
+            .context(format!("Failed to read directory: {:?}", dir))?;
+        for entry in entries {
+            let path = entry.context(format!("Failed to read directory: {:?}", dir))?.path();
+            let metadata = fs::symlink_metadata(&path)
+                .context(format!("Failed to read metadata: {:?}", path))?;
+            if metadata.file_type().is_symlink() {
+                let link_target = fs::read_link(&path)
+                    .context(format!("Failed to read symlink: {:?}", path))?;
+                ensure_link_within(root, &path, &link_target)
+                    .context(format!("Refusing to keep symlink {:?}", path))?;
+            } else if metadata.is_dir() {
+                pending.push(path);
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// 检查位于 `link_path` 的符号链接指向 `root` 内部
+fn ensure_link_within(root: &Path, link_path: &Path, link_target: &Path) -> Result<()> {
+    if link_target.has_root() {
+        return Err(SdkError::invalid_archive(format!("Symlink points to an absolute path: {:?}", link_target)).into());
+    }
+
+    let link_dir = link_path.parent().unwrap_or(root);
+    resolve_within(root, link_dir, link_target, 0)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+    use flate2::write::GzEncoder;
+    use flate2::Compression;
+    use tar::{EntryType, Header};
+    use zip::write::SimpleFileOptions;
+
+    /// (路径, 类型, 链接目标, 内容)
+    type TarFixture<'a> = (&'a str, EntryType, &'a str, &'a [u8]);
+
+    /// 直接写入原始头部字段，绕过 tar::Builder 对 `..` 的检查
+    fn tar_entry(name: &str, entry_type: EntryType, link: &str, data: &[u8]) -> (Header, Vec<u8>) {
+        let mut header = Header::new_old();
+        let old = header.as_old_mut();
+        old.name[..name.len()].copy_from_slice(name.as_bytes());
+        old.linkname[..link.len()].copy_from_slice(link.as_bytes());
+        header.set_entry_type(entry_type);
+        header.set_mode(0o644);
+        header.set_size(data.len() as u64);
+        header.set_cksum();
+        (header, data.to_vec())
+    }
+
+    fn build_tar_gz(path: &Path, entries: &[TarFixture]) {
+        let encoder = GzEncoder::new(fs::File::create(path).unwrap(), Compression::default());
+        let mut builder = tar::Builder::new(encoder);
+        for (name, entry_type, link, data) in entries {
+            let (header, data) = tar_entry(name, *entry_type, link, data);
+            builder.append(&header, data.as_slice()).unwrap();
+        }
+        builder.into_inner().unwrap().finish().unwrap();
+    }
+
+    fn extract_tar(entries: &[TarFixture]) -> (tempfile::TempDir, Result<Extracted>) {
+        let dir = tempfile::tempdir().unwrap();
+        let archive = dir.path().join("fixture.tar.gz");
+        build_tar_gz(&archive, entries);
+        let root = dir.path().join("install");
+        fs::create_dir_all(&root).unwrap();
+        let result = Installer::extract_tar_gz(&archive, &root, &CancellationToken::new(), &mut |_, _| {});
+        (dir, result)
+    }
+
+    fn extract_zip_fixture(build: impl FnOnce(&mut zip::ZipWriter<fs::File>)) -> (tempfile::TempDir, Result<Extracted>) {
+        let dir = tempfile::tempdir().unwrap();
+        let archive = dir.path().join("fixture.zip");
+        let mut writer = zip::ZipWriter::new(fs::File::create(&archive).unwrap());
+        build(&mut writer);
+        writer.finish().unwrap();
+        let root = dir.path().join("install");
+        fs::create_dir_all(&root).unwrap();
+        let result = Installer::extract_zip(&archive, &root, &CancellationToken::new(), &mut |_, _| {});
+        (dir, result)
+    }
+
+    #[test]
+    fn test_tar_extracts_regular_archive() {
+        let (dir, result) = extract_tar(&[
+            ("jdk-21/", EntryType::Directory, "", b""),
+            ("jdk-21/bin/java", EntryType::Regular, "", b"binary"),
+            ("jdk-21/lib/java", EntryType::Symlink, "../bin/java", b""),
+            ("jdk-21/lib/java-hard", EntryType::Link, "jdk-21/bin/java", b""),
+        ]);
+        result.unwrap();
+
+        let root = dir.path().join("install");
+        assert_eq!(fs::read(root.join("bin/java")).unwrap(), b"binary");
+        assert_eq!(fs::read_link(root.join("lib/java")).unwrap(), Path::new("../bin/java"));
+        assert_eq!(fs::read(root.join("lib/java-hard")).unwrap(), b"binary");
+    }
+
+    #[test]
+    fn test_tar_rejects_escaping_entries() {
+        let malicious: Vec<Vec<TarFixture>> = vec![
+            // `..` 路径
+            vec![("jdk/../../evil.txt", EntryType::Regular, "", b"pwned")],
+            // 绝对路径
+            vec![("/jdk/evil.txt", EntryType::Regular, "", b"pwned")],
+            // 指向绝对路径的符号链接
+            vec![("jdk/link", EntryType::Symlink, "/etc", b"")],
+            // 指向安装目录外的相对符号链接
+            vec![("jdk/link", EntryType::Symlink, "../../outside", b"")],
+            // 组合符号链接逃逸
+            vec![
+                ("jdk/a", EntryType::Symlink, ".", b""),
+                ("jdk/b", EntryType::Symlink, "a/..", b""),
+            ],
+            // 反过来的顺序：创建 x 时 y 还不存在
+            vec![
+                ("jdk/x", EntryType::Symlink, "y/..", b""),
+                ("jdk/y", EntryType::Symlink, ".", b""),
+            ],
+            // 之后替换 x 经过的链接
+            vec![
+                ("jdk/sub/", EntryType::Directory, "", b""),
+                ("jdk/y", EntryType::Symlink, "sub", b""),
+                ("jdk/x", EntryType::Symlink, "y/..", b""),
+                ("jdk/y", EntryType::Symlink, ".", b""),
+            ],
+            // 指向安装目录外的硬链接
+            vec![("jdk/hard", EntryType::Link, "jdk/../../secret", b"")],
+            vec![("jdk/hard", EntryType::Link, "/etc/passwd", b"")],
+        ];
+
+        for entries in malicious {
+            let (dir, result) = extract_tar(&entries);
+            assert!(result.is_err(), "archive should be rejected: {:?}", entries[entries.len() - 1].0);
+            assert!(!dir.path().join("evil.txt").exists());
+        }
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_tar_does_not_write_through_symlink() {
+        let outside = tempfile::tempdir().unwrap();
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().join("install");
+        fs::create_dir_all(&root).unwrap();
+        // 预先存在的、指向外部的链接（例如上一次安装遗留）不能被跟随写入
+        std::os::unix::fs::symlink(outside.path(), root.join("escape")).unwrap();
+
+        let archive = dir.path().join("fixture.tar.gz");
+        build_tar_gz(&archive, &[("jdk/escape/evil.txt", EntryType::Regular, "", b"pwned")]);
+        let result = Installer::extract_tar_gz(&archive, &root, &CancellationToken::new(), &mut |_, _| {});
+
+        assert!(result.is_err());
+        assert!(!outside.path().join("evil.txt").exists());
+    }
+
+    #[test]
+    fn test_zip_rejects_escaping_entries() {
+        let (dir, result) = extract_zip_fixture(|writer| {
+            writer.start_file("jdk/../../evil.txt", SimpleFileOptions::default()).unwrap();
+            writer.write_all(b"pwned").unwrap();
+        });
+        assert!(result.is_err());
+        assert!(!dir.path().join("evil.txt").exists());
+
+        let (_dir, result) = extract_zip_fixture(|writer| {
+            writer.start_file("/jdk/evil.txt", SimpleFileOptions::default()).unwrap();
+            writer.write_all(b"pwned").unwrap();
+        });
+        assert!(result.is_err());
+
+        let (_dir, result) = extract_zip_fixture(|writer| {
+            writer.add_symlink("jdk/link", "../../outside", SimpleFileOptions::default()).unwrap();
+        });
+        assert!(result.is_err());
+
+        let (_dir, result) = extract_zip_fixture(|writer| {
+            writer.add_symlink("jdk/link", "/etc/passwd", SimpleFileOptions::default()).unwrap();
+        });
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_replace_installation_swaps_existing_version() {
+        let dir = tempfile::tempdir().unwrap();
+        let install_dir = dir.path().join("21.0.2-tem");
+        let staging_dir = dir.path().join(".staging-21.0.2-tem-1-2");
+        fs::create_dir_all(&install_dir).unwrap();
+        fs::write(install_dir.join("old"), b"old").unwrap();
+        fs::create_dir_all(&staging_dir).unwrap();
+        fs::write(staging_dir.join("new"), b"new").unwrap();
+
+        replace_installation(&staging_dir, &install_dir).unwrap();
+
+        assert!(install_dir.join("new").exists());
+        assert!(!install_dir.join("old").exists());
+        assert!(!staging_dir.exists());
+        assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 1);
+    }
+
+    #[test]
+    fn test_cleanup_stale_staging() {
+        let dir = tempfile::tempdir().unwrap();
+        let java_dir = dir.path().join("java");
+        fs::create_dir_all(java_dir.join("17.0.9-tem")).unwrap();
+        fs::create_dir_all(java_dir.join(".staging-21.0.2-tem-100-200")).unwrap();
+        // 替换中途崩溃：版本目录已移走，只剩备份
+        fs::create_dir_all(java_dir.join(".backup-21.0.2-tem-100-300")).unwrap();
+        // 版本目录已就位，备份可以删除
+        fs::create_dir_all(java_dir.join(".backup-17.0.9-tem-100-400")).unwrap();
+        // 当前进程正在进行的安装
+        fs::create_dir_all(java_dir.join(".staging-22-tem-500-600")).unwrap();
+
+        assert_eq!(cleanup_stale_in(dir.path(), 500).unwrap(), 3);
+
+        let mut remaining: Vec<String> = fs::read_dir(&java_dir).unwrap()
+            .map(|e| e.unwrap().file_name().to_string_lossy().to_string())
+            .collect();
+        remaining.sort();
+        assert_eq!(remaining, vec![".staging-22-tem-500-600", "17.0.9-tem", "21.0.2-tem"]);
+    }
+
+    #[test]
+    fn test_zip_extracts_regular_archive() {
+        let (dir, result) = extract_zip_fixture(|writer| {
+            writer.add_directory("jdk/", SimpleFileOptions::default()).unwrap();
+            writer.start_file("jdk/bin/java", SimpleFileOptions::default()).unwrap();
+            writer.write_all(b"binary").unwrap();
+        });
+        result.unwrap();
+        assert_eq!(fs::read(dir.path().join("install/bin/java")).unwrap(), b"binary");
+    }
+
+    #[test]
+    fn test_validate_staging_rejects_unexpected_layout() {
+        let (dir, result) = extract_zip_fixture(|writer| {
+            writer.start_file("jdk/README", SimpleFileOptions::default()).unwrap();
+            writer.write_all(b"not a JDK").unwrap();
+        });
+        let extracted = result.unwrap();
+        let root = dir.path().join("install");
+
+        // 只有一个无关文件的归档不能作为 java 安装，其他候选者不检查入口
+        let err = Installer::validate_staging(&root, "java", &extracted).unwrap_err();
+        assert!(matches!(err.downcast_ref::<SdkError>(), Some(SdkError::InvalidArchive { .. })));
+        Installer::validate_staging(&root, "maven", &extracted).unwrap();
+
+        // 归档声明了 bin/ 但解压结果中没有
+        let with_bin = Extracted { has_bin: true, ..extracted };
+        assert!(Installer::validate_staging(&root, "maven", &with_bin).is_err());
+
+        // 安装根目录不是目录
+        let err = Installer::validate_staging(&root.join("README"), "maven", &extracted).unwrap_err();
+        assert!(err.to_string().contains("not a directory"), "{}", err);
+
+        fs::create_dir_all(root.join("bin")).unwrap();
+        fs::write(root.join("bin/java"), b"binary").unwrap();
+        Installer::validate_staging(&root, "java", &Extracted { has_bin: true, ..extracted }).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_zip_restores_unix_modes_and_symlinks() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let (dir, result) = extract_zip_fixture(|writer| {
+            let executable = SimpleFileOptions::default().unix_permissions(0o755);
+            let regular = SimpleFileOptions::default().unix_permissions(0o644);
+            writer.start_file("gradle/bin/gradle", executable).unwrap();
+            writer.write_all(b"#!/bin/sh").unwrap();
+            writer.start_file("gradle/bin/gradle.bat", regular).unwrap();
+            writer.write_all(b"@echo off").unwrap();
+            writer.start_file("gradle/lib/helper.sh", executable).unwrap();
+            writer.write_all(b"#!/bin/sh").unwrap();
+            writer.add_symlink("gradle/lib/gradle", "../bin/gradle", SimpleFileOptions::default()).unwrap();
+        });
+        let extracted = result.unwrap();
+        assert!(extracted.has_unix_modes);
+
+        let root = dir.path().join("install");
+        let mode = |path: &str| fs::metadata(root.join(path)).unwrap().permissions().mode() & 0o777;
+        assert_eq!(mode("bin/gradle"), 0o755);
+        assert_eq!(mode("bin/gradle.bat"), 0o644);
+        assert_eq!(mode("lib/helper.sh"), 0o755);
+        assert_eq!(fs::read_link(root.join("lib/gradle")).unwrap(), Path::new("../bin/gradle"));
+        assert_eq!(fs::read(root.join("lib/gradle")).unwrap(), b"#!/bin/sh");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_zip_from_windows_gets_executable_bin() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = tempfile::tempdir().unwrap();
+        let archive = dir.path().join("fixture.zip");
+        let mut writer = zip::ZipWriter::new(fs::File::create(&archive).unwrap());
+        writer.start_file("gradle/bin/gradle", SimpleFileOptions::default()).unwrap();
+        writer.write_all(b"#!/bin/sh").unwrap();
+        writer.start_file("gradle/lib/gradle.jar", SimpleFileOptions::default()).unwrap();
+        writer.write_all(b"jar").unwrap();
+        writer.finish().unwrap();
+
+        // 改写中央目录头：创建系统为 DOS，外部属性只有归档位
+        let mut bytes = fs::read(&archive).unwrap();
+        let headers: Vec<_> = bytes.windows(4)
+            .enumerate()
+            .filter(|(_, w)| *w == b"PK\x01\x02")
+            .map(|(i, _)| i)
+            .collect();
+        assert_eq!(headers.len(), 2);
+        for i in headers {
+            bytes[i + 5] = 0;
+            bytes[i + 38..i + 42].copy_from_slice(&0x20u32.to_le_bytes());
+        }
+        fs::write(&archive, bytes).unwrap();
+
+        let root = dir.path().join("install");
+        fs::create_dir_all(&root).unwrap();
+        let extracted = Installer::extract_zip(&archive, &root, &CancellationToken::new(), &mut |_, _| {}).unwrap();
+        assert!(!extracted.has_unix_modes);
+        Installer::set_executable_permissions(&root).unwrap();
+
+        let mode = |path: &str| fs::metadata(root.join(path)).unwrap().permissions().mode() & 0o777;
+        assert_eq!(mode("bin/gradle"), 0o755);
+        assert_eq!(fs::read(root.join("lib/gradle.jar")).unwrap(), b"jar");
+    }
+}
@@ -0,0 +1,217 @@
+use std::collections::BTreeMap;
+use std::fs::{self, File, OpenOptions, TryLockError};
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant, SystemTime};
+use anyhow::{Result, Context};
+use super::paths;
+use crate::error::SdkError;
+
+/// 锁文件路径（相对于 SDKMAN 根目录）
+const LOCK_FILE: &str = "var/sdkman-gui.lock";
+/// 获取锁的最长等待时间，超时后报告“其他操作正在进行”
+const LOCK_TIMEOUT: Duration = Duration::from_secs(5);
+/// 重试间隔
+const LOCK_RETRY_INTERVAL: Duration = Duration::from_millis(100);
+
+/// SDKMAN 目录的跨进程咨询锁
+///
+/// 修改 `candidates/` 下的版本目录或 `current` 链接前获取，释放时机为 drop；
+/// 外部脚本可以对同一文件执行 `flock` 与本程序互斥
+#[derive(Debug)]
+pub struct SdkmanLock {
+    file: File,
+    path: PathBuf,
+}
+
+impl SdkmanLock {
+    /// 获取 SDKMAN 根目录的锁
+    ///
+    /// 等待期间会阻塞当前线程（最长 5 秒），异步代码需在 `spawn_blocking` 中调用
+    ///
+    /// # Arguments
+    /// * `operation` - 操作描述（如 `install java 21.0.2-tem`），写入锁文件供其他进程报告
+    pub fn acquire(operation: &str) -> Result<Self> {
+        Self::acquire_in(&paths::sdkman_dir()?, operation, LOCK_TIMEOUT)
+    }
+
+    /// 获取指定根目录的锁，超时返回 [`SdkError::Locked`]
+    pub fn acquire_in(root: &Path, operation: &str, timeout: Duration) -> Result<Self> {
+        let path = root.join(LOCK_FILE);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)
+                .context(format!("Failed to create directory: {:?}", parent))?;
+        }
+
+        // 不截断：持有者信息在获取锁之后才覆盖
+        let mut file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(&path)
+            .context(format!("Failed to open lock file: {:?}", path))?;
+
+        let started = Instant::now();
+        loop {
+            match file.try_lock() {
+                Ok(()) => break,
+                Err(TryLockError::WouldBlock) if started.elapsed() < timeout => {
+                    std::thread::sleep(LOCK_RETRY_INTERVAL);
+                }
+                Err(TryLockError::WouldBlock) => {
+                    let holder = read_holder(&path);
+                    println!("SDKMAN directory is locked by {}", holder.as_deref().unwrap_or("another process"));
+                    return Err(SdkError::Locked { holder }.into());
+                }
+                Err(TryLockError::Error(e)) => {
+                    return Err(e).context(format!("Failed to lock {:?}", path));
+                }
+            }
+        }
+
+        // 记录持有者，供其他进程报告
+        let holder = format!("{} (pid {})", operation, std::process::id());
+        let written = file.set_len(0)
+            .and_then(|_| file.seek(SeekFrom::Start(0)))
+            .and_then(|_| file.write_all(holder.as_bytes()));
+        if let Err(e) = written {
+            eprintln!("Warning: Failed to write lock holder to {:?}: {}", path, e);
+        }
+
+        Ok(Self { file, path })
+    }
+}
+
+impl Drop for SdkmanLock {
+    fn drop(&mut self) {
+        let _ = self.file.set_len(0);
+        if let Err(e) = self.file.unlock() {
+            eprintln!("Warning: Failed to unlock {:?}: {}", self.path, e);
+        }
+    }
+}
+
+/// 读取锁文件中的持有者描述（外部进程加锁时可能为空）
+fn read_holder(path: &Path) -> Option<String> {
+    let mut contents = String::new();
+    File::open(path).ok()?.read_to_string(&mut contents).ok()?;
+    let contents = contents.trim();
+    (!contents.is_empty()).then(|| contents.to_string())
+}
+
+/// 候选者目录的快照（版本目录、本地链接和 `current` 指向）
+///
+/// 在获取锁之前记录，获取锁之后重新扫描对比，检测其他进程（如 `sdk` 命令行）
+/// 在等待期间做出的修改
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct CandidateState {
+    /// 条目名 -> (是否为符号链接, 修改时间)
+    entries: BTreeMap<String, (bool, Option<SystemTime>)>,
+    current: Option<PathBuf>,
+}
+
+impl CandidateState {
+    /// 扫描候选者目录（目录不存在时为空），忽略临时目录
+    pub fn capture(candidate_dir: &Path) -> Self {
+        let mut state = Self::default();
+
+        let Ok(entries) = fs::read_dir(candidate_dir) else {
+            return state;
+        };
+        for entry in entries.flatten() {
+            let name = entry.file_name().to_string_lossy().to_string();
+            if name.starts_with('.') {
+                continue;
+            }
+            let Ok(metadata) = entry.path().symlink_metadata() else {
+                continue;
+            };
+            if name == "current" {
+                state.current = fs::read_link(entry.path()).ok();
+                continue;
+            }
+            state.entries.insert(name, (metadata.file_type().is_symlink(), metadata.modified().ok()));
+        }
+
+        state
+    }
+
+    /// 重新扫描，确认 `version` 目录以及指向它的 `current` 链接没有被其他进程修改
+    ///
+    /// 其他版本的变化不影响本次操作，只打印日志；否则返回 [`SdkError::ConcurrentChange`]
+    pub fn ensure_version_unchanged(&self, candidate_dir: &Path, candidate: &str, version: &str) -> Result<()> {
+        let now = Self::capture(candidate_dir);
+        if now == *self {
+            return Ok(());
+        }
+
+        let points_to = |current: &Option<PathBuf>| {
+            current.as_ref().and_then(|target| target.file_name()).is_some_and(|name| name == version)
+        };
+        if self.entries.get(version) != now.entries.get(version)
+            || (self.current != now.current && (points_to(&self.current) || points_to(&now.current)))
+        {
+            return Err(SdkError::ConcurrentChange {
+                candidate: candidate.to_string(),
+                target: version.to_string(),
+            }.into());
+        }
+
+        println!("{:?} changed by another process, {} is not affected", candidate_dir, version);
+        Ok(())
+    }
+
+    /// 重新扫描，确认 `current` 链接没有被其他进程修改，否则返回 [`SdkError::ConcurrentChange`]
+    pub fn ensure_current_unchanged(&self, candidate_dir: &Path, candidate: &str) -> Result<()> {
+        if Self::capture(candidate_dir).current != self.current {
+            return Err(SdkError::ConcurrentChange {
+                candidate: candidate.to_string(),
+                target: "current".to_string(),
+            }.into());
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lock_is_exclusive_and_reports_holder() {
+        let root = tempfile::tempdir().unwrap();
+
+        let lock = SdkmanLock::acquire_in(root.path(), "install java 21.0.2-tem", LOCK_TIMEOUT).unwrap();
+        let err = SdkmanLock::acquire_in(root.path(), "uninstall java 21.0.2-tem", Duration::ZERO).unwrap_err();
+        match err.downcast_ref::<SdkError>() {
+            Some(SdkError::Locked { holder: Some(holder) }) => {
+                assert!(holder.starts_with("install java 21.0.2-tem"), "{}", holder);
+            }
+            other => panic!("unexpected error: {:?}", other),
+        }
+
+        drop(lock);
+        SdkmanLock::acquire_in(root.path(), "uninstall java 21.0.2-tem", Duration::ZERO).unwrap();
+    }
+
+    #[test]
+    fn test_candidate_state_detects_changes() {
+        let candidate_dir = tempfile::tempdir().unwrap();
+        fs::create_dir(candidate_dir.path().join("21.0.2-tem")).unwrap();
+
+        let before = CandidateState::capture(candidate_dir.path());
+        // 临时目录被忽略
+        fs::create_dir(candidate_dir.path().join(".staging-17.0.9-tem-1-1")).unwrap();
+        assert_eq!(CandidateState::capture(candidate_dir.path()), before);
+        before.ensure_version_unchanged(candidate_dir.path(), "java", "17.0.9-tem").unwrap();
+
+        fs::create_dir(candidate_dir.path().join("17.0.9-tem")).unwrap();
+
+        // 其他版本的变化不影响 21.0.2-tem，新出现的 17.0.9-tem 则已被修改
+        before.ensure_version_unchanged(candidate_dir.path(), "java", "21.0.2-tem").unwrap();
+        let err = before.ensure_version_unchanged(candidate_dir.path(), "java", "17.0.9-tem").unwrap_err();
+        assert!(matches!(err.downcast_ref::<SdkError>(), Some(SdkError::ConcurrentChange { .. })));
+        before.ensure_current_unchanged(candidate_dir.path(), "java").unwrap();
+    }
+}
@@ -2,8 +2,14 @@ pub mod scanner;
 pub mod downloader;
 pub mod installer;
 pub mod symlink;
+pub mod checksum;
+pub mod paths;
+pub mod lock;
+pub mod disk;
+pub mod usage;
+pub mod watcher;
 
-pub use scanner::LocalScanner;
+pub use scanner::{InstallKind, LocalScanner};
 pub use downloader::Downloader;
 pub use installer::Installer;
 pub use symlink::SymlinkManager;
@@ -0,0 +1,103 @@
+use std::path::PathBuf;
+use anyhow::{Result, Context};
+use crate::config::AppConfig;
+
+/// SDKMAN 根目录环境变量
+const SDKMAN_DIR_ENV: &str = "SDKMAN_DIR";
+/// 默认的 SDKMAN 根目录（相对于用户主目录）
+const DEFAULT_SDKMAN_DIR: &str = ".sdkman";
+
+/// 获取 SDKMAN 根目录路径
+///
+/// 优先级：配置中的 `sdkman_path` → `SDKMAN_DIR` 环境变量 → `~/.sdkman`
+pub fn sdkman_dir() -> Result<PathBuf> {
+    let configured = AppConfig::load()
+        .map(|config| config.sdkman_path)
+        .unwrap_or_else(|e| {
+            eprintln!("Warning: Failed to load config, using default SDKMAN path: {}", e);
+            String::new()
+        });
+    let env = std::env::var(SDKMAN_DIR_ENV).ok();
+
+    resolve_sdkman_dir(&configured, env.as_deref())
+}
+
+/// 获取候选者目录路径
+pub fn candidate_dir(candidate: &str) -> Result<PathBuf> {
+    Ok(sdkman_dir()?.join("candidates").join(candidate))
+}
+
+/// 按优先级解析 SDKMAN 根目录
+///
+/// 空值和默认值（`~/.sdkman`）不视为显式配置，
+/// 以免旧版本保存的默认路径覆盖 `SDKMAN_DIR`
+fn resolve_sdkman_dir(configured: &str, env: Option<&str>) -> Result<PathBuf> {
+    let default_dir = home_dir()?.join(DEFAULT_SDKMAN_DIR);
+
+    let configured = configured.trim();
+    if !configured.is_empty() {
+        let path = expand_tilde(configured)?;
+        if path != default_dir {
+            return Ok(path);
+        }
+    }
+
+    if let Some(env) = env.map(str::trim).filter(|v| !v.is_empty()) {
+        return expand_tilde(env);
+    }
+
+    Ok(default_dir)
+}
+
+/// 展开路径开头的 `~`
+pub fn expand_tilde(path: &str) -> Result<PathBuf> {
+    if path == "~" {
+        return home_dir();
+    }
+
+    match path.strip_prefix("~/").or_else(|| path.strip_prefix("~\\")) {
+        Some(rest) => Ok(home_dir()?.join(rest)),
+        None => Ok(PathBuf::from(path)),
+    }
+}
+
+fn home_dir() -> Result<PathBuf> {
+    dirs::home_dir().context("Failed to get home directory")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_expand_tilde() {
+        let home = dirs::home_dir().unwrap();
+        assert_eq!(expand_tilde("~").unwrap(), home);
+        assert_eq!(expand_tilde("~/sdks/sdkman").unwrap(), home.join("sdks/sdkman"));
+        assert_eq!(expand_tilde("/opt/sdkman").unwrap(), PathBuf::from("/opt/sdkman"));
+        assert_eq!(expand_tilde("~other/sdkman").unwrap(), PathBuf::from("~other/sdkman"));
+    }
+
+    #[test]
+    fn test_resolve_precedence() {
+        let home = dirs::home_dir().unwrap();
+        let default_dir = home.join(".sdkman");
+
+        // 显式配置优先
+        assert_eq!(
+            resolve_sdkman_dir("/Volumes/dev/sdkman", Some("/opt/sdkman")).unwrap(),
+            PathBuf::from("/Volumes/dev/sdkman")
+        );
+        // 未配置或配置为默认值时使用环境变量
+        assert_eq!(resolve_sdkman_dir("", Some("/opt/sdkman")).unwrap(), PathBuf::from("/opt/sdkman"));
+        assert_eq!(resolve_sdkman_dir("~/.sdkman", Some("/opt/sdkman")).unwrap(), PathBuf::from("/opt/sdkman"));
+        assert_eq!(
+            resolve_sdkman_dir(&default_dir.to_string_lossy(), Some("/opt/sdkman")).unwrap(),
+            PathBuf::from("/opt/sdkman")
+        );
+        // 都没有时使用默认目录
+        assert_eq!(resolve_sdkman_dir("", None).unwrap(), default_dir);
+        assert_eq!(resolve_sdkman_dir("  ", Some("")).unwrap(), default_dir);
+        assert_eq!(resolve_sdkman_dir("~/sdkman", None).unwrap(), home.join("sdkman"));
+    }
+}
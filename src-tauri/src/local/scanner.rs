@@ -1,22 +1,43 @@
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::fs;
 use anyhow::{Result, Context};
+use serde::{Deserialize, Serialize};
+use super::paths;
+use crate::utils::version;
+
+/// 已安装版本的类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum InstallKind {
+    /// 由 SDKMAN（或本应用）下载安装的目录
+    Managed,
+    /// 指向本地目录的符号链接（`sdk install java 17-local /path/to/jdk`）
+    Linked,
+    /// 链接目标已不存在
+    Broken,
+}
+
+/// 候选者目录中的一个已安装条目
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InstalledEntry {
+    pub version: String,
+    pub kind: InstallKind,
+    /// 链接目标（仅 `Linked` 和 `Broken`）
+    pub target: Option<PathBuf>,
+}
 
 /// 本地 SDK 扫描器
 pub struct LocalScanner;
 
 impl LocalScanner {
-    /// 获取 SDKMAN 根目录路径
+    /// 获取 SDKMAN 根目录路径（见 [`paths::sdkman_dir`]）
     fn get_sdkman_dir() -> Result<PathBuf> {
-        let home = dirs::home_dir()
-            .context("Failed to get home directory")?;
-        Ok(home.join(".sdkman"))
+        paths::sdkman_dir()
     }
 
     /// 获取候选者目录路径
     fn get_candidate_dir(candidate: &str) -> Result<PathBuf> {
-        let sdkman_dir = Self::get_sdkman_dir()?;
-        Ok(sdkman_dir.join("candidates").join(candidate))
+        paths::candidate_dir(candidate)
     }
 
     /// 扫描指定 candidate 的已安装版本
@@ -25,45 +46,64 @@ impl LocalScanner {
     /// * `candidate` - SDK 候选者名称（如 "java", "maven"）
     ///
     /// # Returns
-    /// 返回已安装版本的列表（不包括 "current" 符号链接）
+    /// 返回可用的已安装版本列表（包括本地链接版本，不包括损坏的链接、"current" 符号链接和隐藏目录）
     pub fn scan_installed_versions(candidate: &str) -> Result<Vec<String>> {
+        Ok(Self::scan_installed(candidate)?
+            .into_iter()
+            .filter(|entry| entry.kind != InstallKind::Broken)
+            .map(|entry| entry.version)
+            .collect())
+    }
+
+    /// 扫描指定 candidate 的已安装条目，并区分 SDKMAN 安装、本地链接和损坏的链接
+    pub fn scan_installed(candidate: &str) -> Result<Vec<InstalledEntry>> {
         let candidate_dir = Self::get_candidate_dir(candidate)?;
+        Self::scan_candidate_dir(&candidate_dir)
+    }
 
+    pub(super) fn scan_candidate_dir(candidate_dir: &Path) -> Result<Vec<InstalledEntry>> {
         // 如果目录不存在，返回空列表
         if !candidate_dir.exists() {
             return Ok(Vec::new());
         }
 
-        let mut versions = Vec::new();
+        let mut installed = Vec::new();
 
         // 读取目录中的所有条目
-        let entries = fs::read_dir(&candidate_dir)
+        let entries = fs::read_dir(candidate_dir)
             .context(format!("Failed to read directory: {:?}", candidate_dir))?;
 
         for entry in entries {
             let entry = entry?;
             let path = entry.path();
+            let version = entry.file_name().to_string_lossy().to_string();
 
-            // 只处理目录（排除文件）
-            if !path.is_dir() {
+            // 排除 "current" 符号链接和安装过程中的临时目录（.staging-*、.backup-*）
+            if version == "current" || version.starts_with('.') {
                 continue;
             }
 
-            // 获取目录名
-            if let Some(version_name) = path.file_name() {
-                let version_str = version_name.to_string_lossy().to_string();
+            // `sdk install <candidate> <version> <path>` 在候选者目录中创建指向本地目录的符号链接
+            let (kind, target) = if entry.file_type()?.is_symlink() {
+                let target = fs::read_link(&path)
+                    .context(format!("Failed to read symlink: {:?}", path))?;
+                let target = candidate_dir.join(target);
+                let kind = if path.is_dir() { InstallKind::Linked } else { InstallKind::Broken };
+                (kind, Some(target))
+            } else if path.is_dir() {
+                (InstallKind::Managed, None)
+            } else {
+                // 只处理目录（排除文件）
+                continue;
+            };
 
-                // 排除 "current" 符号链接
-                if version_str != "current" {
-                    versions.push(version_str);
-                }
-            }
+            installed.push(InstalledEntry { version, kind, target });
         }
 
-        // 对版本进行排序
-        versions.sort();
+        // 按版本号排序（从旧到新）
+        version::sort_ascending(&mut installed, |entry| entry.version.as_str());
 
-        Ok(versions)
+        Ok(installed)
     }
 
     /// 获取当前默认版本
@@ -175,6 +215,33 @@ mod tests {
         assert!(result.is_ok());
     }
 
+    #[cfg(unix)]
+    #[test]
+    fn test_scan_classifies_linked_and_broken_entries() {
+        use std::os::unix::fs::symlink;
+
+        let root = tempfile::tempdir().unwrap();
+        let candidate_dir = root.path().join("java");
+        let local_jdk = root.path().join("local-jdk");
+        fs::create_dir_all(candidate_dir.join("21.0.2-tem")).unwrap();
+        fs::create_dir_all(candidate_dir.join(".staging-21.0.3-tem")).unwrap();
+        fs::create_dir_all(&local_jdk).unwrap();
+        symlink(&local_jdk, candidate_dir.join("17-local")).unwrap();
+        symlink(root.path().join("removed-jdk"), candidate_dir.join("11-local")).unwrap();
+        symlink("21.0.2-tem", candidate_dir.join("current")).unwrap();
+        fs::write(candidate_dir.join("notes.txt"), "").unwrap();
+
+        let entries = LocalScanner::scan_candidate_dir(&candidate_dir).unwrap();
+        let summary: Vec<(&str, InstallKind)> = entries.iter().map(|e| (e.version.as_str(), e.kind)).collect();
+        assert_eq!(summary, vec![
+            ("11-local", InstallKind::Broken),
+            ("17-local", InstallKind::Linked),
+            ("21.0.2-tem", InstallKind::Managed),
+        ]);
+        assert_eq!(entries[1].target.as_deref(), Some(local_jdk.as_path()));
+        assert_eq!(entries[2].target, None);
+    }
+
     #[test]
     fn test_get_current_version() {
         let result = LocalScanner::get_current_version("java");
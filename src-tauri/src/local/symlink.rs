@@ -1,22 +1,17 @@
 use std::path::PathBuf;
 use std::fs;
 use anyhow::{Result, Context};
+use super::paths;
+use super::lock::{CandidateState, SdkmanLock};
+use crate::error::SdkError;
 
 /// 符号链接管理器
 pub struct SymlinkManager;
 
 impl SymlinkManager {
-    /// 获取 SDKMAN 根目录路径
-    fn get_sdkman_dir() -> Result<PathBuf> {
-        let home = dirs::home_dir()
-            .context("Failed to get home directory")?;
-        Ok(home.join(".sdkman"))
-    }
-
     /// 获取候选者目录路径
     fn get_candidate_dir(candidate: &str) -> Result<PathBuf> {
-        let sdkman_dir = Self::get_sdkman_dir()?;
-        Ok(sdkman_dir.join("candidates").join(candidate))
+        paths::candidate_dir(candidate)
     }
 
     /// 设置默认版本（创建符号链接）
@@ -31,12 +26,20 @@ impl SymlinkManager {
         println!("Setting default version for {} to {}", candidate, version);
 
         let candidates_dir = Self::get_candidate_dir(candidate)?;
+
+        // 加锁后再检查目标版本，避免与 sdk 命令行同时切换 current 链接；
+        // 等待期间被其他进程修改时不覆盖对方的结果
+        let before = CandidateState::capture(&candidates_dir);
+        let _lock = SdkmanLock::acquire(&format!("set default {} {}", candidate, version))?;
+        before.ensure_version_unchanged(&candidates_dir, candidate, version)?;
+        before.ensure_current_unchanged(&candidates_dir, candidate)?;
+
         let current_link = candidates_dir.join("current");
         let target = candidates_dir.join(version);
 
         // 检查目标版本是否存在
         if !target.exists() {
-            anyhow::bail!("Version {} of {} is not installed", version, candidate);
+            return Err(SdkError::not_installed(candidate, version).into());
         }
 
         // 删除旧的符号链接（如果存在）
@@ -80,6 +83,10 @@ impl SymlinkManager {
         println!("Unsetting default version for {}", candidate);
 
         let candidates_dir = Self::get_candidate_dir(candidate)?;
+
+        let before = CandidateState::capture(&candidates_dir);
+        let _lock = SdkmanLock::acquire(&format!("unset default {}", candidate))?;
+        before.ensure_current_unchanged(&candidates_dir, candidate)?;
         let current_link = candidates_dir.join("current");
 
         // 如果符号链接不存在，直接返回成功
@@ -0,0 +1,226 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use std::time::UNIX_EPOCH;
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use super::paths;
+use super::scanner::{InstallKind, LocalScanner};
+use crate::cache;
+use crate::models::{CandidateUsage, DiskUsage, VersionUsage};
+use crate::utils::version;
+
+/// 已统计的版本目录、归档和临时目录大小，按目录修改时间判断是否需要重新统计
+///
+/// 版本目录由安装器整体重命名到位，安装、重新安装都会改变其修改时间；
+/// 归档和临时文件的增删会改变所在目录的修改时间。
+/// 直接修改目录深处的文件（包括下载中的临时文件变大）不会被发现，需要强制刷新
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct SizeIndex {
+    /// 目录路径 -> 大小
+    entries: HashMap<String, SizeEntry>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+struct SizeEntry {
+    /// 目录修改时间（Unix 纳秒）
+    modified: u128,
+    size: u64,
+}
+
+/// 统计 SDKMAN 目录的磁盘占用
+///
+/// # Arguments
+/// * `force` - 忽略缓存，重新统计所有目录
+pub fn disk_usage(force: bool) -> Result<DiskUsage> {
+    let root = paths::sdkman_dir()?;
+
+    let previous = if force {
+        SizeIndex::default()
+    } else {
+        cache::read_disk_usage_cache().unwrap_or_else(|e| {
+            eprintln!("Warning: Failed to read disk usage cache: {}", e);
+            SizeIndex::default()
+        })
+    };
+
+    let (usage, index) = scan_root(&root, &previous);
+
+    if index != previous {
+        if let Err(e) = cache::cache_disk_usage(&index) {
+            eprintln!("Warning: Failed to write disk usage cache: {}", e);
+        }
+    }
+
+    Ok(usage)
+}
+
+/// 统计 `root` 下的候选者、归档和临时目录，返回结果和新的大小索引
+///
+/// 只重新统计修改时间变化的版本目录；已删除的版本不会保留在新索引中
+fn scan_root(root: &Path, previous: &SizeIndex) -> (DiskUsage, SizeIndex) {
+    let mut index = SizeIndex::default();
+    let mut usage = DiskUsage::default();
+    let mut walked = 0;
+
+    let candidates_dir = root.join("candidates");
+    let candidates = fs::read_dir(&candidates_dir)
+        .map(|entries| {
+            entries
+                .flatten()
+                .filter(|entry| entry.path().is_dir())
+                .map(|entry| entry.file_name().to_string_lossy().to_string())
+                .filter(|name| !name.starts_with('.'))
+                .collect::<Vec<_>>()
+        })
+        .unwrap_or_default();
+
+    for candidate in candidates {
+        let candidate_dir = candidates_dir.join(&candidate);
+        let entries = match LocalScanner::scan_candidate_dir(&candidate_dir) {
+            Ok(entries) => entries,
+            Err(e) => {
+                eprintln!("Warning: Failed to scan {:?}: {}", candidate_dir, e);
+                continue;
+            }
+        };
+
+        let mut versions = Vec::new();
+        for entry in entries {
+            // 本地链接指向用户自己的目录，不计入 SDKMAN 的占用
+            let size = if entry.kind == InstallKind::Managed {
+                cached_dir_size(&candidate_dir.join(&entry.version), previous, &mut index, &mut walked)
+            } else {
+                0
+            };
+
+            versions.push(VersionUsage {
+                version: entry.version,
+                size,
+                kind: entry.kind,
+            });
+        }
+
+        version::sort_descending(&mut versions, |v| v.version.as_str());
+        let size = versions.iter().map(|v| v.size).sum();
+        usage.candidates.push(CandidateUsage { candidate, size, versions });
+    }
+
+    usage.candidates.sort_by(|a, b| b.size.cmp(&a.size).then_with(|| a.candidate.cmp(&b.candidate)));
+    usage.archives = cached_dir_size(&root.join("archives"), previous, &mut index, &mut walked);
+    usage.tmp = cached_dir_size(&root.join("tmp"), previous, &mut index, &mut walked);
+    usage.total = usage.candidates.iter().map(|c| c.size).sum::<u64>() + usage.archives + usage.tmp;
+
+    println!("Disk usage: {} bytes ({} directories walked, {} cached)",
+        usage.total, walked, index.entries.len().saturating_sub(walked));
+
+    (usage, index)
+}
+
+/// 目录修改时间与 `previous` 中记录的一致时复用其大小，否则重新统计；结果写入 `index`
+fn cached_dir_size(path: &Path, previous: &SizeIndex, index: &mut SizeIndex, walked: &mut usize) -> u64 {
+    let key = path.to_string_lossy().to_string();
+    let modified = modified_nanos(path);
+
+    let size = match previous.entries.get(&key) {
+        Some(cached) if modified.is_some_and(|m| m == cached.modified) => cached.size,
+        _ => {
+            *walked += 1;
+            dir_size(path)
+        }
+    };
+    if let Some(modified) = modified {
+        index.entries.insert(key, SizeEntry { modified, size });
+    }
+    size
+}
+
+/// 目录修改时间（Unix 纳秒）
+fn modified_nanos(path: &Path) -> Option<u128> {
+    fs::metadata(path)
+        .and_then(|m| m.modified())
+        .ok()
+        .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+        .map(|d| d.as_nanos())
+}
+
+/// 递归统计目录下所有文件的大小（不跟随符号链接，目录不存在时为 0）
+fn dir_size(path: &Path) -> u64 {
+    let mut total = 0;
+    let mut pending = vec![path.to_path_buf()];
+
+    while let Some(dir) = pending.pop() {
+        let entries = match fs::read_dir(&dir) {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => continue,
+            Err(e) => {
+                eprintln!("Warning: Failed to read directory {:?}: {}", dir, e);
+                continue;
+            }
+        };
+
+        for entry in entries.flatten() {
+            let Ok(metadata) = entry.path().symlink_metadata() else {
+                continue;
+            };
+            if metadata.is_dir() {
+                pending.push(entry.path());
+            } else {
+                total += metadata.len();
+            }
+        }
+    }
+
+    total
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write_file(path: &Path, len: usize) {
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, vec![0u8; len]).unwrap();
+    }
+
+    #[test]
+    fn test_scan_root_sizes_and_reuses_cache() {
+        let root = tempfile::tempdir().unwrap();
+        let java = root.path().join("candidates/java");
+        write_file(&java.join("21.0.2-tem/bin/java"), 1000);
+        write_file(&java.join("21.0.2-tem/lib/modules"), 3000);
+        write_file(&java.join("17.0.9-tem/lib/modules"), 2000);
+        write_file(&java.join(".staging-22-tem-1-1/lib/modules"), 5000);
+        write_file(&root.path().join("candidates/maven/3.9.6/lib/maven.jar"), 500);
+        write_file(&root.path().join("archives/java-21.0.2-tem.zip"), 700);
+        write_file(&root.path().join("tmp/download.bin"), 30);
+
+        let (usage, index) = scan_root(root.path(), &SizeIndex::default());
+        assert_eq!(usage.total, 7230);
+        assert_eq!(usage.archives, 700);
+        assert_eq!(usage.tmp, 30);
+        assert_eq!(usage.candidates[0].candidate, "java");
+        assert_eq!(usage.candidates[0].size, 6000);
+        let versions: Vec<_> = usage.candidates[0].versions.iter()
+            .map(|v| (v.version.as_str(), v.size))
+            .collect();
+        assert_eq!(versions, vec![("21.0.2-tem", 4000), ("17.0.9-tem", 2000)]);
+        assert_eq!(index.entries.len(), 5);
+
+        // 修改时间未变化时使用缓存中的大小
+        let mut cached = index.clone();
+        let key = java.join("17.0.9-tem").to_string_lossy().to_string();
+        cached.entries.get_mut(&key).unwrap().size = 42;
+        let key = root.path().join("archives").to_string_lossy().to_string();
+        cached.entries.get_mut(&key).unwrap().size = 7;
+        let (usage, _) = scan_root(root.path(), &cached);
+        assert_eq!(usage.candidates[0].size, 4042);
+        assert_eq!(usage.archives, 7);
+
+        // 卸载的版本从索引中移除
+        fs::remove_dir_all(java.join("17.0.9-tem")).unwrap();
+        let (usage, index) = scan_root(root.path(), &index);
+        assert_eq!(usage.candidates[0].size, 4000);
+        assert_eq!(index.entries.len(), 4);
+    }
+}
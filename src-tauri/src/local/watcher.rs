@@ -0,0 +1,324 @@
+use std::collections::{BTreeSet, HashMap};
+use std::path::{Component, Path, PathBuf};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError};
+use std::sync::{Arc, Mutex, Weak};
+use std::time::{Duration, Instant};
+use anyhow::{Result, Context};
+use notify::event::ModifyKind;
+use notify::{Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter};
+use super::paths;
+use super::scanner::LocalScanner;
+
+/// 最后一次变化之后等待的时间，期间的变化合并为一次事件
+const DEBOUNCE: Duration = Duration::from_millis(500);
+/// 持续变化时最长的合并时间
+const MAX_DEBOUNCE: Duration = Duration::from_secs(3);
+
+/// 已安装状态变化事件（`installed-state-changed`）
+#[derive(Clone, Serialize, Deserialize, Debug, PartialEq, Eq)]
+pub struct InstalledStateChanged {
+    pub candidate: String,
+    /// 已安装版本（升序，不含损坏的链接）
+    pub installed: Vec<String>,
+    /// 当前默认版本
+    pub current: Option<String>,
+}
+
+/// 监听 `candidates/` 及各候选者目录，其他进程（如 `sdk` 命令行）安装、卸载
+/// 或切换默认版本时通知前端
+///
+/// 只监听目录本身（不递归），解压到版本目录内部的大量文件不会产生事件；
+/// `candidates/` 不存在（尚未创建或已被删除）时改为监听 SDKMAN 根目录，出现后再开始监听
+#[derive(Default)]
+pub struct InstalledWatcher {
+    watcher: Mutex<Option<Arc<Mutex<RecommendedWatcher>>>>,
+}
+
+impl InstalledWatcher {
+    /// 按当前配置的 SDKMAN 目录（重新）开始监听
+    pub fn restart(&self, app: &AppHandle) -> Result<()> {
+        // 先停止旧的监听，后台线程随之退出
+        self.watcher.lock().unwrap().take();
+
+        let sdkman_dir = paths::sdkman_dir()?;
+        if !sdkman_dir.is_dir() {
+            anyhow::bail!("SDKMAN directory does not exist: {:?}", sdkman_dir);
+        }
+
+        let (tx, rx) = mpsc::channel();
+        let mut watcher = notify::recommended_watcher(tx)
+            .context("Failed to create file watcher")?;
+
+        let mut watched = Watched::new(sdkman_dir);
+        let states = watched.sync(&mut watcher)
+            .into_iter()
+            .map(|candidate| {
+                let state = scan_state(&candidate);
+                (candidate, state)
+            })
+            .collect();
+        anyhow::ensure!(!watched.dirs.is_empty(), "Failed to watch {:?}", watched.sdkman_dir);
+
+        let watcher = Arc::new(Mutex::new(watcher));
+        let weak = Arc::downgrade(&watcher);
+        let app = app.clone();
+        std::thread::Builder::new()
+            .name("installed-watcher".to_string())
+            .spawn(move || run(watched, rx, weak, states, app))
+            .context("Failed to start file watcher thread")?;
+
+        *self.watcher.lock().unwrap() = Some(watcher);
+        Ok(())
+    }
+}
+
+/// 当前监听的目录
+///
+/// 非递归监听绑定在目录本身上，目录被删除或移走后即失效，同名目录重新创建时需要重新监听
+struct Watched {
+    sdkman_dir: PathBuf,
+    candidates_dir: PathBuf,
+    dirs: BTreeSet<PathBuf>,
+    /// 已失效但可能仍登记在监听器中的目录
+    stale: BTreeSet<PathBuf>,
+}
+
+impl Watched {
+    fn new(sdkman_dir: PathBuf) -> Self {
+        Self {
+            candidates_dir: sdkman_dir.join("candidates"),
+            sdkman_dir,
+            dirs: BTreeSet::new(),
+            stale: BTreeSet::new(),
+        }
+    }
+
+    /// 事件表明 `path` 被删除或移走时，忘记其上的监听
+    fn forget(&mut self, event: &Event) {
+        if !matches!(event.kind, EventKind::Remove(_) | EventKind::Modify(ModifyKind::Name(_))) {
+            return;
+        }
+        for path in &event.paths {
+            if self.dirs.remove(path) {
+                self.stale.insert(path.clone());
+            }
+        }
+    }
+
+    /// 按目录树调整监听：`candidates/` 存在时监听它和其中的候选者目录，否则监听 SDKMAN 根目录
+    ///
+    /// 返回新开始监听的候选者，它们的状态需要重新扫描
+    fn sync(&mut self, watcher: &mut impl Watcher) -> Vec<String> {
+        let attached = self.candidates_dir.is_dir();
+        let desired: BTreeSet<PathBuf> = if attached {
+            list_candidates(&self.candidates_dir)
+                .into_iter()
+                .map(|candidate| self.candidates_dir.join(candidate))
+                .chain([self.candidates_dir.clone()])
+                .collect()
+        } else {
+            [self.sdkman_dir.clone()].into()
+        };
+
+        for dir in self.dirs.difference(&desired).chain(&self.stale) {
+            let _ = watcher.unwatch(dir);
+        }
+        self.dirs.retain(|dir| desired.contains(dir));
+        self.stale.clear();
+
+        let mut added = Vec::new();
+        for dir in desired {
+            if self.dirs.contains(&dir) {
+                continue;
+            }
+            if let Err(e) = watcher.watch(&dir, RecursiveMode::NonRecursive) {
+                eprintln!("Warning: Failed to watch {:?}: {}", dir, e);
+                continue;
+            }
+            if dir == self.candidates_dir {
+                println!("Watching {:?} for installed-state changes", dir);
+            } else if dir == self.sdkman_dir {
+                // 首次安装前或被删除后，candidates/ 不存在
+                println!("Waiting for {:?} to be created", self.candidates_dir);
+            } else if let Some(candidate) = affected_candidate(&self.candidates_dir, &dir) {
+                added.push(candidate);
+            }
+            self.dirs.insert(dir);
+        }
+
+        added
+    }
+}
+
+/// 后台线程：合并变化并按候选者发送事件，监听停止（发送端被丢弃）时退出
+fn run(
+    mut watched: Watched,
+    rx: Receiver<notify::Result<Event>>,
+    watcher: Weak<Mutex<RecommendedWatcher>>,
+    mut states: HashMap<String, InstalledStateChanged>,
+    app: AppHandle,
+) {
+    let candidates_dir = watched.candidates_dir.clone();
+    let mut pending = BTreeSet::new();
+    let mut first_change: Option<Instant> = None;
+
+    loop {
+        let received = match first_change {
+            None => rx.recv().map_err(|_| RecvTimeoutError::Disconnected),
+            Some(first) => {
+                let remaining = MAX_DEBOUNCE.saturating_sub(first.elapsed());
+                rx.recv_timeout(DEBOUNCE.min(remaining))
+            }
+        };
+
+        match received {
+            Ok(Ok(event)) => {
+                if matches!(event.kind, EventKind::Access(_)) {
+                    continue;
+                }
+                // 目录的创建和删除在合并后统一调整监听
+                watched.forget(&event);
+                first_change.get_or_insert_with(Instant::now);
+                for path in &event.paths {
+                    if let Some(candidate) = affected_candidate(&candidates_dir, path) {
+                        pending.insert(candidate);
+                    }
+                }
+            }
+            Ok(Err(e)) => eprintln!("Warning: File watcher error: {}", e),
+            Err(RecvTimeoutError::Timeout) => {
+                first_change = None;
+                if let Some(watcher) = watcher.upgrade() {
+                    pending.extend(watched.sync(&mut *watcher.lock().unwrap()));
+                }
+                for candidate in std::mem::take(&mut pending) {
+                    let state = scan_state(&candidate);
+                    if states.get(&candidate) != Some(&state) {
+                        println!("Installed state of {} changed: {:?} (current: {:?})",
+                            candidate, state.installed, state.current);
+                        let _ = app.emit("installed-state-changed", &state);
+                        states.insert(candidate, state);
+                    }
+                }
+            }
+            Err(RecvTimeoutError::Disconnected) => break,
+        }
+    }
+
+    println!("Stopped watching {:?}", candidates_dir);
+}
+
+/// 扫描候选者的已安装版本和当前版本
+fn scan_state(candidate: &str) -> InstalledStateChanged {
+    InstalledStateChanged {
+        candidate: candidate.to_string(),
+        installed: LocalScanner::scan_installed_versions(candidate).unwrap_or_default(),
+        current: LocalScanner::get_current_version(candidate).unwrap_or(None),
+    }
+}
+
+/// `candidates/` 下的候选者目录（不含隐藏目录）
+fn list_candidates(candidates_dir: &Path) -> Vec<String> {
+    std::fs::read_dir(candidates_dir)
+        .map(|entries| {
+            entries
+                .flatten()
+                .filter(|entry| entry.path().is_dir())
+                .map(|entry| entry.file_name().to_string_lossy().to_string())
+                .filter(|name| !name.starts_with('.'))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// 变化路径所属的候选者
+///
+/// 忽略隐藏文件和安装过程中的临时目录（`.staging-*`、`.backup-*`）
+fn affected_candidate(candidates_dir: &Path, path: &Path) -> Option<String> {
+    let mut components = path.strip_prefix(candidates_dir).ok()?.components();
+
+    let candidate = match components.next()? {
+        Component::Normal(name) => name.to_string_lossy().to_string(),
+        _ => return None,
+    };
+    if candidate.starts_with('.') {
+        return None;
+    }
+
+    if let Some(Component::Normal(entry)) = components.next() {
+        if entry.to_string_lossy().starts_with('.') {
+            return None;
+        }
+    }
+
+    Some(candidate)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_affected_candidate() {
+        let root = Path::new("/home/user/.sdkman/candidates");
+        let candidate = |path: &str| affected_candidate(root, Path::new(path));
+
+        assert_eq!(candidate("/home/user/.sdkman/candidates/java"), Some("java".to_string()));
+        assert_eq!(candidate("/home/user/.sdkman/candidates/java/current"), Some("java".to_string()));
+        assert_eq!(candidate("/home/user/.sdkman/candidates/maven/3.9.6"), Some("maven".to_string()));
+        assert_eq!(candidate("/home/user/.sdkman/candidates/java/.staging-21.0.2-tem-1-2"), None);
+        assert_eq!(candidate("/home/user/.sdkman/candidates/.DS_Store"), None);
+        assert_eq!(candidate("/home/user/.sdkman/candidates"), None);
+        assert_eq!(candidate("/home/user/.sdkman/var/sdkman-gui.lock"), None);
+    }
+
+    #[test]
+    fn test_watched_reattaches_recreated_dirs() {
+        let root = tempfile::tempdir().unwrap();
+        let (tx, rx) = mpsc::channel();
+        let mut watcher = notify::recommended_watcher(tx).unwrap();
+        let mut watched = Watched::new(root.path().to_path_buf());
+
+        // 等待事件安静下来，按删除事件忘记失效的监听，返回收到的路径
+        let settle = |watched: &mut Watched| {
+            let mut paths = Vec::new();
+            while let Ok(event) = rx.recv_timeout(Duration::from_millis(300)) {
+                let event = event.unwrap();
+                watched.forget(&event);
+                paths.extend(event.paths);
+            }
+            paths
+        };
+
+        // candidates/ 不存在时监听根目录
+        assert!(watched.sync(&mut watcher).is_empty());
+        assert_eq!(watched.dirs, BTreeSet::from([root.path().to_path_buf()]));
+
+        let candidates_dir = root.path().join("candidates");
+        std::fs::create_dir_all(candidates_dir.join("java")).unwrap();
+        settle(&mut watched);
+        assert_eq!(watched.sync(&mut watcher), vec!["java"]);
+        assert_eq!(watched.dirs, BTreeSet::from([candidates_dir.clone(), candidates_dir.join("java")]));
+
+        // 删除后立即重新创建：旧的监听已失效，需要重新监听新目录
+        std::fs::remove_dir_all(&candidates_dir).unwrap();
+        std::fs::create_dir_all(candidates_dir.join("java")).unwrap();
+        settle(&mut watched);
+        assert_eq!(watched.sync(&mut watcher), vec!["java"]);
+
+        std::fs::create_dir(candidates_dir.join("java/21.0.2-tem")).unwrap();
+        assert!(settle(&mut watched).contains(&candidates_dir.join("java/21.0.2-tem")));
+
+        // 删除 candidates/ 后回到监听根目录
+        std::fs::remove_dir_all(&candidates_dir).unwrap();
+        settle(&mut watched);
+        assert!(watched.sync(&mut watcher).is_empty());
+        assert_eq!(watched.dirs, BTreeSet::from([root.path().to_path_buf()]));
+
+        std::fs::create_dir_all(candidates_dir.join("maven")).unwrap();
+        assert!(settle(&mut watched).contains(&candidates_dir));
+        assert_eq!(watched.sync(&mut watcher), vec!["maven"]);
+    }
+}
@@ -9,10 +9,16 @@ mod local;
 mod config;
 mod tray;
 mod cache;
+mod jobs;
+mod network;
+mod error;
 
 use std::sync::Arc;
 use tokio::sync::Mutex;
 use api::SdkmanApiClient;
+use jobs::JobManager;
+use local::watcher::InstalledWatcher;
+use network::NetworkState;
 use tauri::Manager;
 
 fn main() {
@@ -21,8 +27,13 @@ fn main() {
         SdkmanApiClient::new().expect("Failed to initialize SDKMAN API client")
     ));
 
+    let max_concurrent_jobs = config::AppConfig::load().unwrap_or_default().max_concurrent_jobs;
+
     tauri::Builder::default()
         .manage(client)
+        .manage(JobManager::new(max_concurrent_jobs))
+        .manage(NetworkState::default())
+        .manage(InstalledWatcher::default())
         .plugin(tauri_plugin_shell::init())
         .plugin(tauri_plugin_dialog::init())
         .plugin(tauri_plugin_single_instance::init(|app, _args, _cwd| {
@@ -36,6 +47,29 @@ fn main() {
         .setup(|app| {
             // 初始化系统托盘
             tray::create_tray(app.handle())?;
+
+            // 监听 sdk 命令行等外部程序对已安装版本的修改
+            if let Err(e) = app.state::<InstalledWatcher>().restart(app.handle()) {
+                eprintln!("Warning: Failed to watch installed SDKs: {}", e);
+            }
+
+            // 清理上次异常退出时遗留的安装临时目录
+            tauri::async_runtime::spawn_blocking(|| {
+                match local::Installer::cleanup_stale_staging() {
+                    Ok(0) => {}
+                    Ok(count) => println!("Cleaned up {} stale installation directories", count),
+                    Err(e) => eprintln!("Warning: Failed to clean up stale installation directories: {}", e),
+                }
+            });
+
+            // 丢弃旧版本未区分平台的版本列表缓存
+            tauri::async_runtime::spawn_blocking(|| {
+                match cache::discard_legacy_caches() {
+                    Ok(0) => {}
+                    Ok(count) => println!("Discarded {} legacy version caches", count),
+                    Err(e) => eprintln!("Warning: Failed to discard legacy version caches: {}", e),
+                }
+            });
             Ok(())
         })
         .on_window_event(|window, event| {
@@ -49,17 +83,22 @@ fn main() {
             commands::list_jdk_versions,
             commands::list_sdk_candidates,
             commands::list_sdk_versions,
+            commands::get_cache_info,
+            commands::get_network_status,
+            commands::check_connectivity,
             commands::get_statistics,
+            commands::get_disk_usage,
             commands::scan_installed_sdks,
             commands::get_current_sdk_version,
             commands::list_installed_candidates,
             commands::is_sdk_installed,
             commands::download_sdk,
-            commands::download_sdk_simple,
             commands::install_sdk,
             commands::uninstall_sdk,
             commands::verify_sdk_installation,
             commands::download_and_install_sdk,
+            commands::list_jobs,
+            commands::cancel_job,
             commands::set_default_sdk_version,
             commands::unset_default_sdk_version,
             commands::load_config,
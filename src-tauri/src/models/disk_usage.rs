@@ -0,0 +1,33 @@
+use serde::{Deserialize, Serialize};
+use crate::local::InstallKind;
+
+/// SDKMAN 目录的磁盘占用（字节）
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct DiskUsage {
+    /// 总占用（候选者 + 归档 + 临时文件）
+    pub total: u64,
+    /// 各候选者占用，按大小从大到小排序
+    pub candidates: Vec<CandidateUsage>,
+    /// `archives/` 目录（SDKMAN 命令行下载的归档）
+    pub archives: u64,
+    /// `tmp/` 目录
+    pub tmp: u64,
+}
+
+/// 候选者的磁盘占用
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct CandidateUsage {
+    pub candidate: String,
+    pub size: u64,
+    /// 各版本占用，按版本号从新到旧排序
+    pub versions: Vec<VersionUsage>,
+}
+
+/// 已安装版本的磁盘占用
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct VersionUsage {
+    pub version: String,
+    /// 本地链接版本不统计链接指向的目录，为 0
+    pub size: u64,
+    pub kind: InstallKind,
+}
@@ -1,7 +1,9 @@
 mod sdk;
 mod sdk_version;
 mod statistics;
+mod disk_usage;
 
 pub use sdk::*;
 pub use sdk_version::*;
 pub use statistics::*;
+pub use disk_usage::*;
@@ -1,5 +1,7 @@
 use serde::{Deserialize, Serialize};
 use std::collections::HashSet;
+use crate::local::checksum::ChecksumStatus;
+use crate::local::InstallKind;
 
 /// SDK版本信息（对应 JavaFX 的 SdkVersion.java）
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -13,6 +15,18 @@ pub struct SdkVersion {
     /// 供应商
     pub vendor: String,
 
+    /// 发行版代码（Java 列表的 Dist 列，如 tem、zulu；其他 SDK 为空）
+    #[serde(default)]
+    pub dist: String,
+
+    /// Java 特性版本号（如 21），其他 SDK 或无法解析时为空
+    #[serde(default, rename = "majorVersion", skip_serializing_if = "Option::is_none")]
+    pub major_version: Option<u32>,
+
+    /// 是否为长期支持版本（由特性版本号推断）
+    #[serde(default)]
+    pub lts: bool,
+
     /// JDK分类集合 - 使用Vec以便正确序列化为JSON数组
     #[serde(default)]
     pub categories: Vec<JdkCategory>,
@@ -38,6 +52,74 @@ pub struct SdkVersion {
     /// 安装进度文本（前端状态）
     #[serde(skip_serializing_if = "Option::is_none")]
     pub install_progress: Option<String>,
+
+    /// 已安装版本的归档校验结果（未记录时为空）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub checksum: Option<ChecksumStatus>,
+
+    /// 本地安装类型（未安装时为空）
+    #[serde(default, rename = "installKind", skip_serializing_if = "Option::is_none")]
+    pub install_kind: Option<InstallKind>,
+
+    /// 本地链接版本指向的目录
+    #[serde(default, rename = "linkTarget", skip_serializing_if = "Option::is_none")]
+    pub link_target: Option<String>,
+}
+
+impl SdkVersion {
+    /// 远程列表中没有、只存在于本地的版本（如 `sdk install java 17-local /path/to/jdk`）
+    pub fn local(candidate: &str, identifier: &str) -> Self {
+        let is_java = candidate == "java";
+        let major_version = if is_java { parse_java_major(identifier) } else { None };
+
+        Self {
+            version: identifier.to_string(),
+            identifier: identifier.to_string(),
+            // 与 SDKMAN 列表中 local only 版本的供应商名称一致
+            vendor: if is_java { "Unclassified".to_string() } else { String::new() },
+            dist: String::new(),
+            major_version,
+            lts: major_version.is_some_and(is_lts_release),
+            categories: if is_java { JdkCategory::from_identifier(identifier) } else { Vec::new() },
+            candidate: candidate.to_string(),
+            installed: false,
+            is_default: false,
+            in_use: false,
+            installing: None,
+            install_progress: None,
+            checksum: None,
+            install_kind: None,
+            link_target: None,
+        }
+    }
+}
+
+/// 从 Java 版本号解析特性版本号
+///
+/// 支持 `21.0.2`、`8.0.392`、`1.8.0`（旧格式），以及 GraalVM/Mandrel 的 `22.3.r17`（取 `r` 后的 Java 版本）
+pub fn parse_java_major(version: &str) -> Option<u32> {
+    let segments: Vec<&str> = version.split(['.', '-', '+', '_']).collect();
+
+    // GraalVM 系列：版本号是 GraalVM 自身版本，Java 版本在 rNN 片段中
+    if let Some(java) = segments
+        .iter()
+        .find_map(|s| s.strip_prefix('r').and_then(|n| n.parse::<u32>().ok()))
+    {
+        return Some(java);
+    }
+
+    let first = segments.first()?.parse::<u32>().ok()?;
+    if first == 1 {
+        // 1.8.0 → 8
+        segments.get(1)?.parse::<u32>().ok()
+    } else {
+        Some(first)
+    }
+}
+
+/// 是否为 LTS 版本：8、11，以及 17 起每两年（每 4 个特性版本）一个
+pub fn is_lts_release(major: u32) -> bool {
+    major == 8 || major == 11 || (major >= 17 && (major - 17).is_multiple_of(4))
 }
 
 /// JDK分类枚举
@@ -141,6 +223,26 @@ mod tests {
         assert!(!categories.contains(&JdkCategory::Nik));
     }
 
+    #[test]
+    fn test_java_major_and_lts() {
+        let cases = [
+            ("21.0.2", Some(21)),
+            ("8.0.392", Some(8)),
+            ("1.8.0", Some(8)),
+            ("11.0.21.fx", Some(11)),
+            ("22.3.r17", Some(17)),
+            ("23.1.2.r21", Some(21)),
+            ("25.ea.3", Some(25)),
+            ("latest", None),
+        ];
+        for (version, expected) in cases {
+            assert_eq!(parse_java_major(version), expected, "{}", version);
+        }
+
+        let lts: Vec<u32> = (6..=29).filter(|m| is_lts_release(*m)).collect();
+        assert_eq!(lts, vec![8, 11, 17, 21, 25, 29]);
+    }
+
     #[test]
     fn test_empty_identifier() {
         let categories = JdkCategory::from_identifier("");
@@ -6,4 +6,8 @@ pub struct Statistics {
     pub jdk_available: usize,
     pub sdk_installed: usize,
     pub sdk_available: usize,
+    /// 是否处于离线模式
+    pub offline: bool,
+    /// 可用数量是否来自过期缓存（或没有缓存）
+    pub stale: bool,
 }
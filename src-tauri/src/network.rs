@@ -0,0 +1,108 @@
+use std::fmt;
+use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+use serde::{Deserialize, Serialize};
+use crate::config::AppConfig;
+
+/// 连续多少次连接失败后自动进入离线模式
+const AUTO_OFFLINE_THRESHOLD: u32 = 2;
+
+/// 离线原因
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OfflineReason {
+    /// 用户在设置中开启离线模式
+    Manual,
+    /// 连续连接失败后自动进入
+    Auto,
+}
+
+/// 网络状态（`network-status` 事件）
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NetworkStatus {
+    pub offline: bool,
+    pub reason: Option<OfflineReason>,
+}
+
+/// 网络状态管理器（保存在 Tauri 状态中）
+#[derive(Default)]
+pub struct NetworkState {
+    failures: AtomicU32,
+    auto_offline: AtomicBool,
+}
+
+impl NetworkState {
+    /// 当前网络状态（手动离线优先）
+    pub fn status(&self, config: &AppConfig) -> NetworkStatus {
+        let reason = if config.offline_mode {
+            Some(OfflineReason::Manual)
+        } else if self.auto_offline.load(Ordering::Relaxed) {
+            Some(OfflineReason::Auto)
+        } else {
+            None
+        };
+
+        NetworkStatus {
+            offline: reason.is_some(),
+            reason,
+        }
+    }
+
+    /// 记录一次连接失败，返回是否因此进入自动离线模式
+    pub fn record_failure(&self) -> bool {
+        let failures = self.failures.fetch_add(1, Ordering::Relaxed) + 1;
+        failures >= AUTO_OFFLINE_THRESHOLD && !self.auto_offline.swap(true, Ordering::Relaxed)
+    }
+
+    /// 记录一次成功请求，返回是否因此退出自动离线模式
+    pub fn record_success(&self) -> bool {
+        self.failures.store(0, Ordering::Relaxed);
+        self.auto_offline.swap(false, Ordering::Relaxed)
+    }
+}
+
+/// 离线时执行需要网络的操作返回的错误
+#[derive(Debug, Clone)]
+pub struct Offline {
+    /// 被阻止的操作
+    pub operation: String,
+}
+
+impl Offline {
+    pub fn new(operation: impl Into<String>) -> Self {
+        Self { operation: operation.into() }
+    }
+}
+
+impl fmt::Display for Offline {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Offline: {} requires a network connection", self.operation)
+    }
+}
+
+impl std::error::Error for Offline {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_auto_offline_after_failures() {
+        let state = NetworkState::default();
+        let config = AppConfig::default();
+
+        assert!(!state.record_failure());
+        assert!(!state.status(&config).offline);
+
+        // 第二次失败进入自动离线，之后的失败不再重复通知
+        assert!(state.record_failure());
+        assert!(!state.record_failure());
+        assert_eq!(state.status(&config).reason, Some(OfflineReason::Auto));
+
+        assert!(state.record_success());
+        assert!(!state.record_success());
+        assert!(!state.status(&config).offline);
+
+        let manual = AppConfig { offline_mode: true, ..AppConfig::default() };
+        assert_eq!(state.status(&manual).reason, Some(OfflineReason::Manual));
+    }
+}
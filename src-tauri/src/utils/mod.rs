@@ -1 +1 @@
-// Utility modules will be added here
+pub mod version;
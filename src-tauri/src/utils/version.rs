@@ -0,0 +1,232 @@
+use std::cmp::Ordering;
+
+/// 预发布限定符的排序等级（越小越早），正式版为 `RELEASE_RANK`
+const RELEASE_RANK: u8 = 5;
+/// 未知限定符（如 `fx`、`crac`、`r17`）排在同版本号的正式版之后
+const UNKNOWN_QUALIFIER_RANK: u8 = 6;
+
+/// 版本号中的一个片段
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Token {
+    /// 数字（去掉前导零，避免超长数字溢出）
+    Number(String),
+    /// 字母限定符（小写）
+    Qualifier(String),
+}
+
+impl Token {
+    fn qualifier_rank(qualifier: &str) -> u8 {
+        match qualifier {
+            "snapshot" | "dev" | "nightly" => 0,
+            "ea" | "alpha" | "a" => 1,
+            "beta" | "b" => 2,
+            "milestone" | "m" => 3,
+            "rc" | "cr" | "pre" | "preview" => 4,
+            "final" | "ga" | "release" => RELEASE_RANK,
+            _ => UNKNOWN_QUALIFIER_RANK,
+        }
+    }
+
+    /// 与版本号结尾比较（`1.0` 等同于 `1.0.0`，`1.0-rc1` 早于 `1.0`）
+    fn cmp_end(&self) -> Ordering {
+        match self {
+            Token::Number(n) if n.is_empty() => Ordering::Equal,
+            Token::Number(_) => Ordering::Greater,
+            Token::Qualifier(q) => Self::qualifier_rank(q).cmp(&RELEASE_RANK),
+        }
+    }
+}
+
+impl Ord for Token {
+    fn cmp(&self, other: &Self) -> Ordering {
+        match (self, other) {
+            (Token::Number(a), Token::Number(b)) => a.len().cmp(&b.len()).then_with(|| a.cmp(b)),
+            // 数字总是晚于限定符：1.0.1 > 1.0-rc1，1.0.1 > 1.0.fx
+            (Token::Number(_), Token::Qualifier(_)) => Ordering::Greater,
+            (Token::Qualifier(_), Token::Number(_)) => Ordering::Less,
+            (Token::Qualifier(a), Token::Qualifier(b)) => Self::qualifier_rank(a)
+                .cmp(&Self::qualifier_rank(b))
+                .then_with(|| a.cmp(b)),
+        }
+    }
+}
+
+impl PartialOrd for Token {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// SDKMAN 版本标识符（如 `21.0.2-tem`、`17.0.9.fx-zulu`、`3.9.6`、`4.0.0-rc-1`）
+///
+/// 按数字片段、预发布限定符、供应商后缀的顺序比较
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Version {
+    raw: String,
+    tokens: Vec<Token>,
+    vendor: Option<String>,
+}
+
+impl Version {
+    pub fn parse(identifier: &str) -> Self {
+        let (version, vendor) = split_vendor(identifier);
+        Self {
+            raw: identifier.to_string(),
+            tokens: tokenize(version),
+            vendor: vendor.map(str::to_string),
+        }
+    }
+}
+
+impl Ord for Version {
+    fn cmp(&self, other: &Self) -> Ordering {
+        let len = self.tokens.len().max(other.tokens.len());
+        for i in 0..len {
+            let ordering = match (self.tokens.get(i), other.tokens.get(i)) {
+                (Some(a), Some(b)) => a.cmp(b),
+                (Some(a), None) => a.cmp_end(),
+                (None, Some(b)) => b.cmp_end().reverse(),
+                (None, None) => Ordering::Equal,
+            };
+            if ordering != Ordering::Equal {
+                return ordering;
+            }
+        }
+
+        // 版本号相同时按供应商、原始字符串排序，保证顺序稳定
+        self.vendor.cmp(&other.vendor).then_with(|| self.raw.cmp(&other.raw))
+    }
+}
+
+impl PartialOrd for Version {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// 按版本从旧到新排序
+pub fn sort_ascending<T>(items: &mut [T], identifier: impl Fn(&T) -> &str) {
+    items.sort_by_cached_key(|item| Version::parse(identifier(item)));
+}
+
+/// 按版本从新到旧排序
+pub fn sort_descending<T>(items: &mut [T], identifier: impl Fn(&T) -> &str) {
+    items.sort_by_cached_key(|item| std::cmp::Reverse(Version::parse(identifier(item))));
+}
+
+/// 拆分末尾的供应商后缀（`-` 之后的纯字母片段，预发布限定符除外）
+fn split_vendor(identifier: &str) -> (&str, Option<&str>) {
+    if let Some((version, suffix)) = identifier.rsplit_once('-') {
+        let is_alphabetic = !suffix.is_empty() && suffix.chars().all(|c| c.is_ascii_alphabetic());
+        let is_qualifier = Token::qualifier_rank(&suffix.to_ascii_lowercase()) != UNKNOWN_QUALIFIER_RANK;
+        if is_alphabetic && !is_qualifier && !version.is_empty() {
+            return (version, Some(suffix));
+        }
+    }
+    (identifier, None)
+}
+
+/// 按分隔符（`.`、`-`、`_`、`+`）以及数字/字母边界切分
+fn tokenize(version: &str) -> Vec<Token> {
+    let mut tokens = Vec::new();
+    let mut current = String::new();
+
+    let mut flush = |current: &mut String| {
+        if current.is_empty() {
+            return;
+        }
+        let token = if current.starts_with(|c: char| c.is_ascii_digit()) {
+            Token::Number(current.trim_start_matches('0').to_string())
+        } else {
+            Token::Qualifier(current.to_ascii_lowercase())
+        };
+        tokens.push(token);
+        current.clear();
+    };
+
+    for c in version.chars() {
+        if matches!(c, '.' | '-' | '_' | '+') {
+            flush(&mut current);
+            continue;
+        }
+        let boundary = current
+            .chars()
+            .last()
+            .is_some_and(|last| last.is_ascii_digit() != c.is_ascii_digit());
+        if boundary {
+            flush(&mut current);
+        }
+        current.push(c);
+    }
+    flush(&mut current);
+
+    tokens
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn compare(a: &str, b: &str) -> Ordering {
+        Version::parse(a).cmp(&Version::parse(b))
+    }
+
+    #[test]
+    fn test_version_ordering() {
+        // (较早的版本, 较晚的版本)
+        let cases = [
+            ("9.0.1", "21.0.2"),
+            ("3.9", "3.10"),
+            ("3.9.6", "3.10.0"),
+            ("1.0", "1.0.1"),
+            ("8.0.392-tem", "21.0.2-tem"),
+            ("17.0.9-tem", "17.0.10-tem"),
+            ("11.0.21-zulu", "17.0.1-tem"),
+            ("1.0-rc1", "1.0"),
+            ("1.0-rc1", "1.0-rc2"),
+            ("1.0.0-alpha", "1.0.0-beta"),
+            ("1.0.0-beta-2", "1.0.0-rc-1"),
+            ("4.0.0-alpha-10", "4.0.0-beta-1"),
+            ("4.0.0-M1", "4.0.0-RC1"),
+            ("2.0-SNAPSHOT", "2.0-alpha"),
+            ("3.4.0-RC1", "3.4.0"),
+            ("1.0-rc1", "1.0.1"),
+            ("17.0.9-zulu", "17.0.9.fx-zulu"),
+            ("17.0.9.fx-zulu", "17.0.10-zulu"),
+            ("22.3.r11-grl", "22.3.r17-grl"),
+            ("22.3.r17-grl", "23.0.1-graal"),
+            ("2.7.18", "2.7.18.1"),
+            ("1.9.24", "1.10.0"),
+            ("20230101", "20230102"),
+            ("99999999999999999999", "100000000000000000000"),
+        ];
+
+        for (older, newer) in cases {
+            assert_eq!(compare(older, newer), Ordering::Less, "{} < {}", older, newer);
+            assert_eq!(compare(newer, older), Ordering::Greater, "{} > {}", newer, older);
+        }
+
+        // 数字片段等价，但排序仍然稳定
+        assert_eq!(Version::parse("1.0").tokens, Version::parse("1.00").tokens);
+        assert_ne!(compare("1.0", "1.0.0"), Ordering::Equal);
+        assert_eq!(compare("21.0.2-tem", "21.0.2-tem"), Ordering::Equal);
+        // 版本号相同时按供应商排序
+        assert_eq!(compare("21.0.2-amzn", "21.0.2-tem"), Ordering::Less);
+    }
+
+    #[test]
+    fn test_vendor_suffix() {
+        assert_eq!(Version::parse("21.0.2-tem").vendor.as_deref(), Some("tem"));
+        assert_eq!(Version::parse("17.0.9.fx-zulu").vendor.as_deref(), Some("zulu"));
+        assert_eq!(Version::parse("1.0.0-rc").vendor.as_deref(), None);
+        assert_eq!(Version::parse("2.0-SNAPSHOT").vendor.as_deref(), None);
+        assert_eq!(Version::parse("3.4.0-RC1").vendor.as_deref(), None);
+        assert_eq!(Version::parse("3.9.6").vendor.as_deref(), None);
+
+        let mut versions = vec!["3.10", "21.0.2", "3.9", "9.0.1", "3.10-rc1"];
+        sort_descending(&mut versions, |v| v);
+        assert_eq!(versions, vec!["21.0.2", "9.0.1", "3.10", "3.10-rc1", "3.9"]);
+        sort_ascending(&mut versions, |v| v);
+        assert_eq!(versions, vec!["3.9", "3.10-rc1", "3.10", "9.0.1", "21.0.2"]);
+    }
+}
@@ -0,0 +1,53 @@
+import i18n from './index'
+
+// 与 Rust 后端 ErrorCode 保持一致
+export type ErrorCode =
+  | 'not_installed'
+  | 'not_found'
+  | 'network'
+  | 'offline'
+  | 'http_status'
+  | 'incomplete_download'
+  | 'checksum_mismatch'
+  | 'checksum_missing'
+  | 'invalid_archive'
+  | 'malformed_response'
+  | 'operation_in_progress'
+  | 'insufficient_space'
+  | 'concurrent_change'
+  | 'permission_denied'
+  | 'cancelled'
+  | 'job_not_found'
+  | 'job_in_progress'
+  | 'io'
+  | 'internal'
+
+// 命令返回的结构化错误
+export interface CommandError {
+  code: ErrorCode
+  message: string
+  context: string[]
+  retryable: boolean
+  params: Record<string, string>
+}
+
+export function isCommandError(e: unknown): e is CommandError {
+  return typeof e === 'object' && e !== null && 'code' in e && 'message' in e
+}
+
+// 重试是否可能成功
+export function isRetryable(e: unknown): boolean {
+  return isCommandError(e) && e.retryable
+}
+
+// 将命令错误转换为当前语言的提示，没有对应翻译时使用后端信息
+export function describeError(e: unknown): string {
+  if (!isCommandError(e)) {
+    return (e as any)?.message || String(e)
+  }
+  const key = `errors.${e.code}`
+  if (e.code === 'internal' || !i18n.global.te(key)) {
+    return e.message
+  }
+  return i18n.global.t(key, e.params)
+}
@@ -1,6 +1,7 @@
 import { defineStore } from 'pinia'
 import { ref } from 'vue'
 import { listen, type UnlistenFn } from '@tauri-apps/api/event'
+import { invoke } from '@tauri-apps/api/core'
 
 export interface ProgressInfo {
   type: 'download' | 'install'
@@ -12,7 +13,9 @@ export interface InstallTask {
   candidate: string
   version: string
   identifier: string
-  status: 'downloading' | 'installing' | 'completed' | 'failed'
+  jobId?: string  // 后端任务ID，用于取消
+  status: 'queued' | 'downloading' | 'installing' | 'completed' | 'failed' | 'cancelled'
+  queuePosition?: number  // 排队位置（从 1 开始）
   progress: ProgressInfo
 }
 
@@ -24,21 +27,72 @@ export const useInstallProgressStore = defineStore('installProgress', () => {
   let unlistenDownload: UnlistenFn | null = null
   let unlistenInstall: UnlistenFn | null = null
   let unlistenComplete: UnlistenFn | null = null
+  let unlistenJobStarted: UnlistenFn | null = null
+  let unlistenJobCancelled: UnlistenFn | null = null
+  let unlistenJobQueue: UnlistenFn | null = null
 
   // 初始化事件监听器（在应用启动时调用一次）
   async function initEventListeners() {
     // 如果已经初始化，先清理
-    if (unlistenDownload || unlistenInstall || unlistenComplete) {
+    if (unlistenDownload || unlistenInstall || unlistenComplete || unlistenJobStarted || unlistenJobCancelled || unlistenJobQueue) {
       await cleanupEventListeners()
     }
 
+    // 监听任务开始，记录任务ID
+    unlistenJobStarted = await listen<any>('job-started', (event) => {
+      const { job_id, candidate, version } = event.payload
+      const task = tasks.value.get(`${candidate}-${version}`)
+      if (task) {
+        task.jobId = job_id
+        tasks.value = new Map(tasks.value)
+      }
+    })
+
+    // 监听任务队列变化（排队位置、开始执行）
+    unlistenJobQueue = await listen<any[]>('job-queue', (event) => {
+      let changed = false
+      for (const job of event.payload) {
+        const task = tasks.value.get(`${job.candidate}-${job.version}`)
+        if (!task || task.status === 'cancelled') continue
+
+        if (job.state === 'queued') {
+          task.status = 'queued'
+          task.queuePosition = job.position
+          changed = true
+        } else if (task.status === 'queued') {
+          task.status = 'downloading'
+          task.queuePosition = undefined
+          task.progress = {
+            type: 'download',
+            percentage: 0,
+            message: 'Starting...'
+          }
+          changed = true
+        }
+      }
+      if (changed) {
+        // 触发响应式更新
+        tasks.value = new Map(tasks.value)
+      }
+    })
+
+    // 监听任务取消
+    unlistenJobCancelled = await listen<any>('job-cancelled', (event) => {
+      const { candidate, version } = event.payload
+      const task = tasks.value.get(`${candidate}-${version}`)
+      if (task) {
+        task.status = 'cancelled'
+        tasks.value = new Map(tasks.value)
+      }
+    })
+
     // 监听下载进度
     unlistenDownload = await listen<any>('download-progress', (event) => {
       const { candidate, version, percentage, downloaded, total } = event.payload
       const identifier = `${candidate}-${version}`
 
       const task = tasks.value.get(identifier)
-      if (task) {
+      if (task && task.status !== 'cancelled') {
         task.status = 'downloading'
         task.progress = {
           type: 'download',
@@ -56,7 +110,7 @@ export const useInstallProgressStore = defineStore('installProgress', () => {
       const identifier = `${candidate}-${version}`
 
       const task = tasks.value.get(identifier)
-      if (task) {
+      if (task && task.status !== 'cancelled') {
         task.status = 'installing'
         // 安装时保持下载完成的进度条状态，只更新消息
         task.progress = {
@@ -120,6 +174,18 @@ export const useInstallProgressStore = defineStore('installProgress', () => {
       unlistenComplete()
       unlistenComplete = null
     }
+    if (unlistenJobStarted) {
+      unlistenJobStarted()
+      unlistenJobStarted = null
+    }
+    if (unlistenJobCancelled) {
+      unlistenJobCancelled()
+      unlistenJobCancelled = null
+    }
+    if (unlistenJobQueue) {
+      unlistenJobQueue()
+      unlistenJobQueue = null
+    }
   }
 
   // 开始一个安装任务
@@ -149,6 +215,22 @@ export const useInstallProgressStore = defineStore('installProgress', () => {
     tasks.value = new Map(tasks.value)
   }
 
+  // 取消一个任务（等待后端发送 job-cancelled 事件）
+  async function cancelTask(identifier: string) {
+    const jobId = tasks.value.get(identifier)?.jobId
+    if (!jobId) return
+    try {
+      await invoke('cancel_job', { jobId })
+    } catch (e) {
+      console.error('[InstallProgressStore] Failed to cancel job:', e)
+    }
+  }
+
+  // 检查某个任务是否已被取消
+  function isCancelled(identifier: string): boolean {
+    return tasks.value.get(identifier)?.status === 'cancelled'
+  }
+
   // 检查某个版本是否正在操作中
   function isOperating(identifier: string): boolean {
     return tasks.value.has(identifier)
@@ -185,6 +267,8 @@ export const useInstallProgressStore = defineStore('installProgress', () => {
     cleanupEventListeners,
     startTask,
     removeTask,
+    cancelTask,
+    isCancelled,
     isOperating,
     getProgress,
     getTaskStatus,
@@ -1,11 +1,20 @@
 import {defineStore} from 'pinia'
 import {invoke} from '@tauri-apps/api/core'
+import {listen, type UnlistenFn} from '@tauri-apps/api/event'
 import {ref} from 'vue'
+import {describeError} from '../i18n/errors'
+
+export interface ChecksumStatus {
+  status: 'verified' | 'unverified'
+  algorithm?: string
+}
 
 export interface SdkVersion {
   version: string
   vendor: string
-  dist: string
+  dist: string  // 发行版代码（Java 列表的 Dist 列）
+  majorVersion?: number  // Java 特性版本号（如 21）
+  lts: boolean  // 是否为长期支持版本
   status: string
   identifier: string
   installed: boolean
@@ -13,6 +22,9 @@ export interface SdkVersion {
   isDefault: boolean  // 匹配 Rust 后端的 camelCase 命名
   categories: string[]  // JDK分类数组：JDK, JAVAFX, NIK
   candidate: string
+  checksum?: ChecksumStatus  // 已安装版本的归档校验结果
+  installKind?: 'managed' | 'linked' | 'broken'  // 本地安装类型：SDKMAN 安装、本地链接、链接失效
+  linkTarget?: string  // 本地链接版本指向的目录
 }
 
 export interface Sdk {
@@ -28,6 +40,71 @@ export interface Statistics {
   jdk_available: number
   sdk_installed: number
   sdk_available: number
+  offline: boolean  // 是否处于离线模式
+  stale: boolean  // 可用数量是否来自过期缓存
+}
+
+// 磁盘占用（对应后端 DiskUsage，单位字节）
+export interface VersionUsage {
+  version: string
+  size: number
+  kind: 'managed' | 'linked' | 'broken'
+}
+
+export interface CandidateUsage {
+  candidate: string
+  size: number
+  versions: VersionUsage[]  // 按版本号从新到旧
+}
+
+export interface DiskUsage {
+  total: number
+  candidates: CandidateUsage[]  // 按大小从大到小
+  archives: number
+  tmp: number
+}
+
+// 网络状态（对应后端 NetworkStatus）
+export interface NetworkStatus {
+  offline: boolean
+  reason: 'manual' | 'auto' | null
+}
+
+// 实际提供数据的 API 端点（对应后端 api-endpoint 事件）
+export interface EndpointUsed {
+  request: 'candidates' | 'versions' | 'download'
+  candidate: string | null
+  endpoint: string
+  mirror: boolean
+}
+
+// 列表缓存状态（对应后端 CacheInfo）
+export interface CacheInfo {
+  kind: 'candidates' | 'versions'
+  candidate: string | null
+  fetched_at: number  // Unix 时间戳（秒）
+  ttl_secs: number
+  stale: boolean
+  offline: boolean  // 最近一次获取失败或处于离线模式，显示的是缓存数据
+}
+
+// 后台刷新完成事件（对应后端 cache-updated 事件）
+export interface CacheUpdated {
+  kind: 'candidates' | 'versions'
+  candidate: string | null
+  fetched_at: number
+}
+
+// 已安装状态变化事件（对应后端 installed-state-changed 事件，由文件监听触发）
+export interface InstalledStateChanged {
+  candidate: string
+  installed: string[]
+  current: string | null
+}
+
+// 格式化列表获取时间（旧格式缓存没有时间戳，返回 null）
+export function formatFetchedAt(info: CacheInfo): string | null {
+  return info.fetched_at > 0 ? new Date(info.fetched_at * 1000).toLocaleString() : null
 }
 
 export const useSdkStore = defineStore('sdk', () => {
@@ -39,7 +116,11 @@ export const useSdkStore = defineStore('sdk', () => {
     jdk_available: 0,
     sdk_installed: 0,
     sdk_available: 0,
+    offline: false,
+    stale: false,
   })
+  const networkStatus = ref<NetworkStatus>({ offline: false, reason: null })
+  const diskUsage = ref<DiskUsage | null>(null)
   const installedJdkVersions = ref<string[]>([])
   const currentJdkVersion = ref<string | null>(null)
   const loading = ref(false)
@@ -48,6 +129,150 @@ export const useSdkStore = defineStore('sdk', () => {
   // 跟踪正在进行的操作，防止重复点击
   const uninstallingVersions = ref<Set<string>>(new Set())
 
+  // 最近一次请求使用的端点，key 为 "candidates" 或 "versions:java" 等
+  const servedEndpoints = ref<Record<string, EndpointUsed>>({})
+  // 列表缓存状态，key 与 servedEndpoints 相同
+  const cacheInfo = ref<Record<string, CacheInfo>>({})
+  // 外部程序（如 sdk 命令行）修改后的已安装状态，key 为候选者名称
+  const installedStates = ref<Record<string, InstalledStateChanged>>({})
+  let unlistenEndpoint: UnlistenFn | null = null
+  let unlistenCacheUpdated: UnlistenFn | null = null
+  let unlistenCacheStale: UnlistenFn | null = null
+  let unlistenNetworkStatus: UnlistenFn | null = null
+  let unlistenInstalledState: UnlistenFn | null = null
+
+  function endpointKey(request: string, candidate: string | null): string {
+    return candidate ? `${request}:${candidate}` : request
+  }
+
+  // 初始化事件监听器（在应用启动时调用一次）
+  async function initEventListeners() {
+    cleanupEventListeners()
+
+    unlistenEndpoint = await listen<EndpointUsed>('api-endpoint', (event) => {
+      const used = event.payload
+      servedEndpoints.value = {
+        ...servedEndpoints.value,
+        [endpointKey(used.request, used.candidate)]: used,
+      }
+      if (used.mirror) {
+        console.log(`[api-endpoint] ${used.request} served by mirror ${used.endpoint}`)
+      }
+    })
+
+    // 后台刷新完成：更新缓存状态，JDK 和候选者列表直接重新加载，
+    // 其他 SDK 的详情页监听 cacheInfo 变化自行重新加载
+    unlistenCacheUpdated = await listen<CacheUpdated>('cache-updated', async (event) => {
+      const { kind, candidate } = event.payload
+      console.log('[cache-updated]', endpointKey(kind, candidate))
+      if (kind === 'candidates') {
+        await fetchSdkCandidates()
+      } else if (candidate === 'java') {
+        await fetchJdkVersions()
+      } else {
+        await fetchCacheInfo(kind, candidate)
+      }
+    })
+
+    // 获取失败回退到缓存数据：更新缓存状态，界面据此标注
+    unlistenCacheStale = await listen<CacheInfo>('cache-stale', (event) => {
+      const info = event.payload
+      console.log('[cache-stale]', endpointKey(info.kind, info.candidate))
+      cacheInfo.value = { ...cacheInfo.value, [endpointKey(info.kind, info.candidate)]: info }
+    })
+
+    // 已安装版本或默认版本在磁盘上发生变化：JDK 列表直接重新加载，
+    // 其他 SDK 的详情页和列表页监听 installedStates 变化自行重新加载
+    unlistenInstalledState = await listen<InstalledStateChanged>('installed-state-changed', async (event) => {
+      const state = event.payload
+      console.log('[installed-state-changed]', state.candidate, state.installed, state.current)
+      installedStates.value = { ...installedStates.value, [state.candidate]: state }
+      // 已显示磁盘占用时重新统计（只会遍历变化的版本目录）
+      if (diskUsage.value) {
+        fetchDiskUsage()
+      }
+      if (state.candidate === 'java') {
+        installedJdkVersions.value = state.installed
+        currentJdkVersion.value = state.current
+        await fetchJdkVersions()
+      }
+    })
+
+    unlistenNetworkStatus = await listen<NetworkStatus>('network-status', (event) => {
+      console.log('[network-status]', event.payload)
+      networkStatus.value = event.payload
+    })
+    await fetchNetworkStatus()
+  }
+
+  function cleanupEventListeners() {
+    if (unlistenEndpoint) {
+      unlistenEndpoint()
+      unlistenEndpoint = null
+    }
+    if (unlistenCacheUpdated) {
+      unlistenCacheUpdated()
+      unlistenCacheUpdated = null
+    }
+    if (unlistenCacheStale) {
+      unlistenCacheStale()
+      unlistenCacheStale = null
+    }
+    if (unlistenNetworkStatus) {
+      unlistenNetworkStatus()
+      unlistenNetworkStatus = null
+    }
+    if (unlistenInstalledState) {
+      unlistenInstalledState()
+      unlistenInstalledState = null
+    }
+  }
+
+  async function fetchNetworkStatus() {
+    try {
+      networkStatus.value = await invoke<NetworkStatus>('get_network_status')
+    } catch (e) {
+      console.error('Failed to get network status:', e)
+    }
+  }
+
+  // 重新检测网络连接（自动离线模式下使用）
+  async function checkConnectivity() {
+    try {
+      networkStatus.value = await invoke<NetworkStatus>('check_connectivity')
+    } catch (e) {
+      console.error('Failed to check connectivity:', e)
+    }
+    return !networkStatus.value.offline
+  }
+
+  // 刷新列表缓存状态
+  async function fetchCacheInfo(kind: 'candidates' | 'versions', candidate: string | null = null) {
+    try {
+      const info = await invoke<CacheInfo | null>('get_cache_info', { kind, candidate })
+      const key = endpointKey(kind, candidate)
+      const next = { ...cacheInfo.value }
+      if (info) {
+        next[key] = info
+      } else {
+        delete next[key]
+      }
+      cacheInfo.value = next
+    } catch (e) {
+      console.error('Failed to get cache info:', e)
+    }
+  }
+
+  // 获取列表最后获取时间
+  function listCacheInfo(kind: 'candidates' | 'versions', candidate: string | null = null): CacheInfo | null {
+    return cacheInfo.value[endpointKey(kind, candidate)] ?? null
+  }
+
+  // 获取某类请求最近使用的端点
+  function servedEndpoint(request: string, candidate: string | null = null): EndpointUsed | null {
+    return servedEndpoints.value[endpointKey(request, candidate)] ?? null
+  }
+
   // Actions
   async function fetchJdkVersions(forceRefresh = false) {
     loading.value = true
@@ -57,8 +282,9 @@ export const useSdkStore = defineStore('sdk', () => {
       jdkVersions.value = await invoke<SdkVersion[]>('list_jdk_versions', { forceRefresh })
       console.log('[fetchJdkVersions] Fetched', jdkVersions.value.length, 'versions.',
         'Installed:', jdkVersions.value.filter(v => v.installed).length)
+      await fetchCacheInfo('versions', 'java')
     } catch (e) {
-      error.value = String(e)
+      error.value = describeError(e)
       console.error('Failed to fetch JDK versions:', e)
     } finally {
       loading.value = false
@@ -70,8 +296,9 @@ export const useSdkStore = defineStore('sdk', () => {
     error.value = null
     try {
       sdkCandidates.value = await invoke<Sdk[]>('list_sdk_candidates', { forceRefresh })
+      await fetchCacheInfo('candidates')
     } catch (e) {
-      error.value = String(e)
+      error.value = describeError(e)
       console.error('Failed to fetch SDK candidates:', e)
     } finally {
       loading.value = false
@@ -84,13 +311,22 @@ export const useSdkStore = defineStore('sdk', () => {
     try {
       statistics.value = await invoke<Statistics>('get_statistics')
     } catch (e) {
-      error.value = String(e)
+      error.value = describeError(e)
       console.error('Failed to fetch statistics:', e)
     } finally {
       loading.value = false
     }
   }
 
+  // 统计磁盘占用（未变化的版本目录使用后端缓存）
+  async function fetchDiskUsage(forceRefresh = false) {
+    try {
+      diskUsage.value = await invoke<DiskUsage>('get_disk_usage', { forceRefresh })
+    } catch (e) {
+      console.error('Failed to get disk usage:', e)
+    }
+  }
+
   async function scanInstalledJdks() {
     try {
       installedJdkVersions.value = await invoke<string[]>('scan_installed_sdks', {
@@ -121,7 +357,7 @@ export const useSdkStore = defineStore('sdk', () => {
       // 刷新 JDK 版本列表以更新状态
       await fetchJdkVersions()
     } catch (e) {
-      error.value = String(e)
+      error.value = describeError(e)
       console.error('Failed to set default JDK version:', e)
       throw e
     }
@@ -141,7 +377,7 @@ export const useSdkStore = defineStore('sdk', () => {
         fetchStatistics()
       ])
     } catch (e) {
-      error.value = String(e)
+      error.value = describeError(e)
       console.error('Failed to install JDK:', e)
       throw e
     }
@@ -182,7 +418,7 @@ export const useSdkStore = defineStore('sdk', () => {
       await new Promise(resolve => setTimeout(resolve, 50))
       console.log('[uninstallJdk] Complete')
     } catch (e) {
-      error.value = String(e)
+      error.value = describeError(e)
       console.error('Failed to uninstall JDK:', e)
       throw e
     } finally {
@@ -202,7 +438,9 @@ export const useSdkStore = defineStore('sdk', () => {
   // 通用的SDK管理方法（用于非Java的SDK）
   async function listSdkVersions(candidate: string, forceRefresh = false): Promise<SdkVersion[]> {
     try {
-      return await invoke<SdkVersion[]>('list_sdk_versions', {candidate, forceRefresh})
+      const versions = await invoke<SdkVersion[]>('list_sdk_versions', {candidate, forceRefresh})
+      await fetchCacheInfo('versions', candidate)
+      return versions
     } catch (e) {
       console.error(`Failed to list ${candidate} versions:`, e)
       return []
@@ -217,7 +455,7 @@ export const useSdkStore = defineStore('sdk', () => {
         version
       })
     } catch (e) {
-      error.value = String(e)
+      error.value = describeError(e)
       console.error(`Failed to install ${candidate}:`, e)
       throw e
     }
@@ -243,7 +481,7 @@ export const useSdkStore = defineStore('sdk', () => {
       })
       console.log('[uninstallSdk] Uninstall completed')
     } catch (e) {
-      error.value = String(e)
+      error.value = describeError(e)
       console.error('[uninstallSdk] Failed to uninstall', candidate, ':', e)
       throw e
     } finally {
@@ -259,7 +497,7 @@ export const useSdkStore = defineStore('sdk', () => {
         version
       })
     } catch (e) {
-      error.value = String(e)
+      error.value = describeError(e)
       console.error(`Failed to set default ${candidate} version:`, e)
       throw e
     }
@@ -279,11 +517,17 @@ export const useSdkStore = defineStore('sdk', () => {
     currentJdkVersion,
     loading,
     error,
+    servedEndpoints,
+    cacheInfo,
+    installedStates,
+    networkStatus,
+    diskUsage,
 
     // Actions
     fetchJdkVersions,
     fetchSdkCandidates,
     fetchStatistics,
+    fetchDiskUsage,
     scanInstalledJdks,
     getCurrentJdkVersion,
     setDefaultJdkVersion,
@@ -295,5 +539,12 @@ export const useSdkStore = defineStore('sdk', () => {
     uninstallSdk,
     setDefaultSdkVersion,
     isUninstalling,
+    initEventListeners,
+    cleanupEventListeners,
+    servedEndpoint,
+    fetchCacheInfo,
+    listCacheInfo,
+    fetchNetworkStatus,
+    checkConnectivity,
   }
 })